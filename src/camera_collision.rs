@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+
+use crate::{
+    orbit_camera::CameraSettings, physics::RigidBody, picking::SceneRaycast,
+    smoothing::smooth_towards,
+};
+
+/// Keeps [`CameraSettings`] cameras with a [`CameraCollision`] out of the scene: the focus rests
+/// on the surface under it, and the camera moves in towards the focus when something blocks
+/// the view of it.
+pub struct CameraCollisionPlugin;
+
+impl Plugin for CameraCollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (follow_terrain, avoid_occlusion)
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Camera collision settings.
+///
+/// Only the drawn [`Transform`] is pulled in, so the orbit distance the user chose comes back
+/// once the view is clear again.
+#[derive(Component, Debug)]
+pub struct CameraCollision {
+    /// Whether the focus moves up and down to the surface under it when it is panned.
+    ///
+    /// A focus that was raised or lowered, such as by framing or by leaving fly mode, stays at
+    /// that height until it is panned again. Moving bodies are ignored, so the focus does not
+    /// jump onto whatever passes under it.
+    pub follow_terrain: bool,
+    /// Radius of the sphere swept from the focus towards the camera.
+    pub radius: f32,
+    /// The closest the camera is pulled in to the focus.
+    pub min_distance: f32,
+    /// Time in seconds for the camera to get halfway back out once the view clears. It always
+    /// moves in at once.
+    pub recover_half_life: f32,
+    /// How far from the focus the camera is while pulled in.
+    distance: Option<f32>,
+    /// The focus as [`follow_terrain`] last saw or left it.
+    last_focus: Option<Vec3>,
+}
+
+impl Default for CameraCollision {
+    fn default() -> Self {
+        Self {
+            follow_terrain: true,
+            radius: 0.3,
+            min_distance: 0.5,
+            recover_half_life: 0.2,
+            distance: None,
+            last_focus: None,
+        }
+    }
+}
+
+/// How far above the focus the surface below it is searched for.
+const TERRAIN_PROBE_HEIGHT: f32 = 100.0;
+/// How far the focus can be from the surface before it is moved.
+const TERRAIN_TOLERANCE: f32 = 1e-3;
+/// How close to its orbit distance a recovering camera has to get to count as back.
+const RECOVERED_TOLERANCE: f32 = 1e-4;
+
+fn follow_terrain(
+    mut cameras: Query<(&mut CameraSettings, &mut CameraCollision)>,
+    mut raycast: SceneRaycast,
+    bodies: Query<&RigidBody>,
+    parents: Query<&ChildOf>,
+) {
+    // Parts of a body are as likely to move as the body itself.
+    let is_static = |entity: Entity| {
+        std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .all(|entity| {
+                bodies
+                    .get(entity)
+                    .ok()
+                    .is_none_or(|body| *body == RigidBody::Static)
+            })
+    };
+
+    for (mut camera_settings, mut collision) in &mut cameras {
+        if !collision.follow_terrain || !camera_settings.enabled {
+            continue;
+        }
+
+        // Panning slides the focus sideways at the same height. A new height was chosen on
+        // purpose, and is kept.
+        let focus = camera_settings.should_focus_at;
+        let panned = collision.last_focus.is_none_or(|last| {
+            (last.y - focus.y).abs() <= TERRAIN_TOLERANCE && last.xz() != focus.xz()
+        });
+        collision.last_focus = Some(focus);
+        if !panned {
+            continue;
+        }
+
+        let ray = Ray3d::new(focus + Vec3::Y * TERRAIN_PROBE_HEIGHT, Dir3::NEG_Y);
+        let Some(hit) = raycast.cast_ray_filtered(ray, 2.0 * TERRAIN_PROBE_HEIGHT, is_static)
+        else {
+            continue;
+        };
+        if (hit.point.y - focus.y).abs() > TERRAIN_TOLERANCE {
+            camera_settings.should_focus_at.y = hit.point.y;
+            collision.last_focus = Some(camera_settings.should_focus_at);
+        }
+    }
+}
+
+fn avoid_occlusion(
+    time: Res<Time>,
+    mut cameras: Query<(&mut Transform, &CameraSettings, &mut CameraCollision)>,
+    mut raycast: SceneRaycast,
+) {
+    let dt = time.delta_secs();
+
+    for (mut transform, camera_settings, mut collision) in &mut cameras {
+        if !camera_settings.enabled || !camera_settings.initialized {
+            continue;
+        }
+
+        let pose = camera_settings.current;
+        let full_distance = pose.orbit_distance;
+        let Ok(direction) = Dir3::new(pose.translation() - pose.should_focus_at) else {
+            continue;
+        };
+        // Start one radius out, so a focus resting on a surface does not count as blocked.
+        let start = collision.radius.min(full_distance);
+        let ray = Ray3d::new(pose.should_focus_at + direction * start, direction);
+        let clear = raycast
+            .cast_sphere_filtered(ray, collision.radius, full_distance - start, |_| true)
+            .map_or(full_distance, |hit| start + hit.distance)
+            .max(collision.min_distance.min(full_distance));
+
+        let mut distance = collision.distance.unwrap_or(full_distance);
+        if clear < distance {
+            distance = clear;
+        } else {
+            smooth_towards(&mut distance, &clear, collision.recover_half_life, dt);
+        }
+
+        if distance >= full_distance - RECOVERED_TOLERANCE {
+            if collision.distance.is_some() {
+                collision.distance = None;
+            }
+        } else {
+            collision.distance = Some(distance);
+            transform.translation = pose.should_focus_at + direction * distance;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera_controller::CameraController,
+        camera_mode::CameraModeSwitch,
+        framing::{FrameEntities, FrameTarget},
+        physics::Collider,
+        test_harness::TestApp,
+    };
+    use bevy::render::primitives::Aabb;
+
+    /// The height of the top of [`spawn_hill`].
+    const HILL_HEIGHT: f32 = 2.0;
+
+    /// A camera that follows the terrain of [`spawn_hill`], with the focus at the origin.
+    fn following_terrain(hill: std::ops::Range<f32>) -> TestApp {
+        let mut app = TestApp::looking_at_origin();
+        spawn_hill(&mut app, hill);
+        // Let the hill's transform propagate before the camera looks for it.
+        app.update();
+        let camera = app.camera();
+        app.world_mut()
+            .entity_mut(camera)
+            .insert(CameraCollision::default());
+        app
+    }
+
+    /// A flat-topped hill covering `x_range` and everything near the origin along Z.
+    fn spawn_hill(app: &mut TestApp, x_range: std::ops::Range<f32>) {
+        let half_extents = Vec3::new((x_range.end - x_range.start) / 2.0, HILL_HEIGHT / 2.0, 50.0);
+        let center = Vec3::new((x_range.start + x_range.end) / 2.0, HILL_HEIGHT / 2.0, 0.0);
+        app.world_mut().spawn((
+            Collider::cuboid(half_extents),
+            Transform::from_translation(center),
+        ));
+    }
+
+    #[test]
+    fn panning_onto_a_hill_rests_the_focus_on_it() {
+        let mut app = following_terrain(-50.0..-1.0);
+        app.update();
+        assert_eq!(app.settings().should_focus_at.y, 0.0);
+
+        app.drag(MouseButton::Left, Vec2::new(300.0, 0.0), 10);
+        app.update();
+
+        let focus = app.settings().should_focus_at;
+        assert!(focus.x < -1.0, "focus at {focus} should be over the hill");
+        assert!((focus.y - HILL_HEIGHT).abs() < 1e-4, "focus at {focus}");
+    }
+
+    #[test]
+    fn framing_keeps_the_focus_off_the_terrain() {
+        let mut app = following_terrain(-50.0..50.0);
+        app.update();
+        assert_eq!(app.settings().should_focus_at.y, HILL_HEIGHT);
+
+        let center = Vec3::new(1.0, 5.0, -1.0);
+        let target = app
+            .world_mut()
+            .spawn((
+                Mesh3d::default(),
+                Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5)),
+                Transform::from_translation(center),
+            ))
+            .id();
+        let camera = app.camera();
+        app.world_mut().send_event(FrameEntities {
+            camera,
+            target: FrameTarget::Hierarchy(target),
+        });
+        app.settle();
+
+        assert_eq!(app.settings().should_focus_at, center);
+    }
+
+    #[test]
+    fn leaving_fly_mode_keeps_a_focus_in_the_air() {
+        let mut app = following_terrain(-50.0..50.0);
+        let camera = app.camera();
+        app.world_mut()
+            .entity_mut(camera)
+            .insert((CameraController::default(), CameraModeSwitch::default()));
+        let key_toggle = CameraModeSwitch::default().key_toggle;
+        app.update();
+
+        app.press_key(key_toggle);
+        app.update();
+        app.release_key(key_toggle);
+        // Look up, away from the ground, so the focus ends up straight ahead in the air.
+        let mut controller = app.world_mut().get_mut::<CameraController>(camera).unwrap();
+        controller.pitch = 0.5;
+        controller.current_pitch = 0.5;
+        app.update();
+
+        app.press_key(key_toggle);
+        app.update();
+        app.release_key(key_toggle);
+        let focus = app.settings().should_focus_at;
+        assert!(
+            focus.y > HILL_HEIGHT + 1.0,
+            "focus at {focus} should be above the hill"
+        );
+        app.settle();
+
+        assert_eq!(app.settings().should_focus_at, focus);
+    }
+}
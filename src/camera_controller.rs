@@ -1,10 +1,12 @@
-use bevy::{
-    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
-    prelude::*,
-    window::CursorGrabMode,
-};
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*};
 use std::{f32::consts::*, fmt};
 
+use crate::{
+    cursor_grab::{CursorGrab, CursorGrabRequest},
+    input_map::{Action, ActionInput, InputMap, axis_response, stick_response},
+    smoothing::{smooth_towards, smoothed_displacement},
+};
+
 /// A freecam-style camera controller plugin.
 pub struct CameraControllerPlugin;
 
@@ -28,38 +30,34 @@ pub struct CameraController {
     pub initialized: bool,
     /// Multiplier for pitch and yaw rotation speed.
     pub sensitivity: f32,
-    /// [`KeyCode`] for forward translation.
-    pub key_forward: KeyCode,
-    /// [`KeyCode`] for backward translation.
-    pub key_back: KeyCode,
-    /// [`KeyCode`] for left translation.
-    pub key_left: KeyCode,
-    /// [`KeyCode`] for right translation.
-    pub key_right: KeyCode,
-    /// [`KeyCode`] for up translation.
-    pub key_up: KeyCode,
-    /// [`KeyCode`] for down translation.
-    pub key_down: KeyCode,
-    /// [`KeyCode`] to use [`run_speed`](CameraController::run_speed) instead of
-    /// [`walk_speed`](CameraController::walk_speed) for translation.
-    pub key_run: KeyCode,
-    /// [`MouseButton`] for grabbing the mouse focus.
-    pub mouse_key_cursor_grab: MouseButton,
-    /// [`KeyCode`] for grabbing the keyboard focus.
-    pub keyboard_key_toggle_cursor_grab: KeyCode,
+    /// Fraction of a gamepad stick's or trigger's travel that is ignored around its rest position.
+    pub stick_dead_zone: f32,
+    /// Exponent applied to gamepad stick and trigger deflection; values above one give finer
+    /// control near the centre.
+    pub stick_response_exponent: f32,
+    /// Radians per second to turn at full [`Action::LookX`] and [`Action::LookY`] deflection.
+    pub gamepad_look_speed: f32,
     /// Multiplier for unmodified translation speed.
     pub walk_speed: f32,
-    /// Multiplier for running translation speed.
+    /// Multiplier for translation speed while [`Action::Run`] is held.
     pub run_speed: f32,
-    /// Multiplier for how the mouse scroll wheel modifies [`walk_speed`](CameraController::walk_speed)
+    /// Multiplier for how [`Action::Zoom`] modifies [`walk_speed`](CameraController::walk_speed)
     /// and [`run_speed`](CameraController::run_speed).
     pub scroll_factor: f32,
-    /// Friction factor used to exponentially decay [`velocity`](CameraController::velocity) over time.
-    pub friction: f32,
-    /// This [`CameraController`]'s pitch rotation.
+    /// Seconds for [`velocity`](CameraController::velocity) to cover half the remaining way to
+    /// the speed requested by the keys, so it eases in and decays to rest when they are released.
+    pub velocity_half_life: f32,
+    /// Seconds for the displayed pitch and yaw to cover half the remaining way to
+    /// [`pitch`](CameraController::pitch) and [`yaw`](CameraController::yaw).
+    pub rotation_half_life: f32,
+    /// This [`CameraController`]'s target pitch rotation.
     pub pitch: f32,
-    /// This [`CameraController`]'s yaw rotation.
+    /// This [`CameraController`]'s target yaw rotation.
     pub yaw: f32,
+    /// This [`CameraController`]'s displayed pitch rotation.
+    pub current_pitch: f32,
+    /// This [`CameraController`]'s displayed yaw rotation.
+    pub current_yaw: f32,
     /// This [`CameraController`]'s translation velocity.
     pub velocity: Vec3,
 }
@@ -70,62 +68,70 @@ impl Default for CameraController {
             enabled: true,
             initialized: false,
             sensitivity: 0.25,
-            key_forward: KeyCode::KeyW,
-            key_back: KeyCode::KeyS,
-            key_left: KeyCode::KeyA,
-            key_right: KeyCode::KeyD,
-            key_up: KeyCode::KeyE,
-            key_down: KeyCode::KeyQ,
-            key_run: KeyCode::ShiftLeft,
-            mouse_key_cursor_grab: MouseButton::Left,
-            keyboard_key_toggle_cursor_grab: KeyCode::KeyM,
+            stick_dead_zone: 0.15,
+            stick_response_exponent: 2.0,
+            gamepad_look_speed: PI,
             walk_speed: 5.0,
             run_speed: 15.0,
             scroll_factor: 0.1,
-            friction: 0.5,
+            // Roughly what halving the velocity every frame at 60 FPS used to feel like.
+            velocity_half_life: 1.0 / 60.0,
+            rotation_half_life: 0.02,
             pitch: 0.0,
             yaw: 0.0,
+            current_pitch: 0.0,
+            current_yaw: 0.0,
             velocity: Vec3::ZERO,
         }
     }
 }
 
-impl fmt::Display for CameraController {
+/// Lists the freecam controls as bound in an [`InputMap`].
+struct FreecamControls<'a>(&'a InputMap);
+
+impl fmt::Display for FreecamControls<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let map = self.0;
         write!(
             f,
             "
 Freecam Controls:
     Mouse\t- Move camera orientation
-    Scroll\t- Adjust movement speed
-    {:?}\t- Hold to grab cursor
-    {:?}\t- Toggle cursor grab
-    {:?} & {:?}\t- Fly forward & backwards
-    {:?} & {:?}\t- Fly sideways left & right
-    {:?} & {:?}\t- Fly up & down
-    {:?}\t- Fly faster while held",
-            self.mouse_key_cursor_grab,
-            self.keyboard_key_toggle_cursor_grab,
-            self.key_forward,
-            self.key_back,
-            self.key_left,
-            self.key_right,
-            self.key_up,
-            self.key_down,
-            self.key_run,
+    {}\t- Adjust movement speed
+    {}\t- Hold to grab cursor
+    {}\t- Toggle cursor grab
+    {} & {}\t- Fly forward & backwards
+    {} & {}\t- Fly sideways left & right
+    {} & {}\t- Fly up & down
+    {}\t- Fly faster while held
+    {} & {}\t- Fly sideways & forward (analog)
+    {}\t- Fly up & down (analog)
+    {} & {}\t- Look around (analog)",
+            map.describe(Action::Zoom),
+            map.describe(Action::GrabCursor),
+            map.describe(Action::ToggleCursorGrab),
+            map.describe(Action::MoveForward),
+            map.describe(Action::MoveBack),
+            map.describe(Action::MoveLeft),
+            map.describe(Action::MoveRight),
+            map.describe(Action::MoveUp),
+            map.describe(Action::MoveDown),
+            map.describe(Action::Run),
+            map.describe(Action::MoveX),
+            map.describe(Action::MoveZ),
+            map.describe(Action::MoveY),
+            map.describe(Action::LookX),
+            map.describe(Action::LookY),
         )
     }
 }
 
 fn run_camera_controller(
     time: Res<Time>,
-    mut windows: Query<&mut Window>,
+    cursor_grab: Res<CursorGrab>,
+    mut cursor_grab_requests: EventWriter<CursorGrabRequest>,
     accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
-    accumulated_mouse_scroll: Res<AccumulatedMouseScroll>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    key_input: Res<ButtonInput<KeyCode>>,
-    mut toggle_cursor_grab: Local<bool>,
-    mut mouse_cursor_grab: Local<bool>,
+    actions: ActionInput,
     mut query: Query<(&mut Transform, &mut CameraController), With<Camera>>,
 ) {
     let dt = time.delta_secs();
@@ -138,108 +144,206 @@ fn run_camera_controller(
         let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
         controller.yaw = yaw;
         controller.pitch = pitch;
+        controller.current_yaw = yaw;
+        controller.current_pitch = pitch;
         controller.initialized = true;
-        info!("{}", *controller);
+        info!("{}", FreecamControls(&actions.map));
     }
     if !controller.enabled {
         return;
     }
 
-    let mut scroll = 0.0;
-
-    let amount = match accumulated_mouse_scroll.unit {
-        MouseScrollUnit::Line => accumulated_mouse_scroll.delta.y,
-        MouseScrollUnit::Pixel => accumulated_mouse_scroll.delta.y / 16.0,
-    };
-    scroll += amount;
+    let scroll = actions.value(Action::Zoom);
     controller.walk_speed += scroll * controller.scroll_factor * controller.walk_speed;
     controller.run_speed = controller.walk_speed * 3.0;
 
     // Handle key input
     let mut axis_input = Vec3::ZERO;
-    if key_input.pressed(controller.key_forward) {
+    if actions.pressed(Action::MoveForward) {
         axis_input.z += 1.0;
     }
-    if key_input.pressed(controller.key_back) {
+    if actions.pressed(Action::MoveBack) {
         axis_input.z -= 1.0;
     }
-    if key_input.pressed(controller.key_right) {
+    if actions.pressed(Action::MoveRight) {
         axis_input.x += 1.0;
     }
-    if key_input.pressed(controller.key_left) {
+    if actions.pressed(Action::MoveLeft) {
         axis_input.x -= 1.0;
     }
-    if key_input.pressed(controller.key_up) {
+    if actions.pressed(Action::MoveUp) {
         axis_input.y += 1.0;
     }
-    if key_input.pressed(controller.key_down) {
+    if actions.pressed(Action::MoveDown) {
         axis_input.y -= 1.0;
     }
 
-    let mut cursor_grab_change = false;
-    if key_input.just_pressed(controller.keyboard_key_toggle_cursor_grab) {
-        *toggle_cursor_grab = !*toggle_cursor_grab;
-        cursor_grab_change = true;
+    // Handle cursor grab
+    if actions.just_pressed(Action::ToggleCursorGrab) {
+        cursor_grab_requests.write(CursorGrabRequest::Toggle);
     }
-    if mouse_button_input.just_pressed(controller.mouse_key_cursor_grab) {
-        *mouse_cursor_grab = true;
-        cursor_grab_change = true;
+    if actions.just_pressed(Action::GrabCursor) {
+        cursor_grab_requests.write(CursorGrabRequest::Grab);
     }
-    if mouse_button_input.just_released(controller.mouse_key_cursor_grab) {
-        *mouse_cursor_grab = false;
-        cursor_grab_change = true;
+    if actions.just_released(Action::GrabCursor) {
+        cursor_grab_requests.write(CursorGrabRequest::Release);
     }
-    let cursor_grab = *mouse_cursor_grab || *toggle_cursor_grab;
+
+    // Handle gamepad input, which keeps its magnitude so the stick controls the speed
+    let (dead_zone, exponent) = (
+        controller.stick_dead_zone,
+        controller.stick_response_exponent,
+    );
+    let stick = stick_response(
+        Vec2::new(actions.value(Action::MoveX), actions.value(Action::MoveZ)),
+        dead_zone,
+        exponent,
+    );
+    let lift = axis_response(
+        actions.value(Action::MoveY).clamp(-1.0, 1.0),
+        dead_zone,
+        exponent,
+    );
+    let analog_input = Vec3::new(stick.x, lift, stick.y).clamp_length_max(1.0);
 
     // Apply movement update
-    if axis_input != Vec3::ZERO {
-        let max_speed = if key_input.pressed(controller.key_run) {
-            controller.run_speed
-        } else {
-            controller.walk_speed
-        };
-        controller.velocity = axis_input.normalize() * max_speed;
+    let max_speed = if actions.pressed(Action::Run) {
+        controller.run_speed
     } else {
-        let friction = controller.friction.clamp(0.0, 1.0);
-        controller.velocity *= 1.0 - friction;
-        if controller.velocity.length_squared() < 1e-6 {
-            controller.velocity = Vec3::ZERO;
-        }
+        controller.walk_speed
+    };
+    let target_velocity = if axis_input != Vec3::ZERO {
+        axis_input.normalize() * max_speed
+    } else {
+        analog_input * max_speed
+    };
+    let velocity_half_life = controller.velocity_half_life;
+    let displacement =
+        smoothed_displacement(controller.velocity, target_velocity, velocity_half_life, dt);
+    smooth_towards(
+        &mut controller.velocity,
+        &target_velocity,
+        velocity_half_life,
+        dt,
+    );
+    if target_velocity == Vec3::ZERO && controller.velocity.length_squared() < 1e-6 {
+        controller.velocity = Vec3::ZERO;
     }
     let forward = *transform.forward();
-    println!("Forward: {}", forward);
     let right = *transform.right();
-    transform.translation += controller.velocity.x * dt * right
-        + controller.velocity.y * dt * Vec3::Y
-        + controller.velocity.z * dt * forward;
-
-    // Handle cursor grab
-    if cursor_grab_change {
-        if cursor_grab {
-            for mut window in &mut windows {
-                if !window.focused {
-                    continue;
-                }
-
-                window.cursor_options.grab_mode = CursorGrabMode::Locked;
-                window.cursor_options.visible = false;
-            }
-        } else {
-            for mut window in &mut windows {
-                window.cursor_options.grab_mode = CursorGrabMode::None;
-                window.cursor_options.visible = true;
-            }
-        }
-    }
+    transform.translation +=
+        displacement.x * right + displacement.y * Vec3::Y + displacement.z * forward;
 
     // Handle mouse input
-    if accumulated_mouse_motion.delta != Vec2::ZERO && cursor_grab {
+    if accumulated_mouse_motion.delta != Vec2::ZERO && cursor_grab.is_grabbed() {
         // Apply look update
         controller.pitch = (controller.pitch
             - accumulated_mouse_motion.delta.y * RADIANS_PER_DOT * controller.sensitivity)
             .clamp(-PI / 2., PI / 2.);
         controller.yaw -=
             accumulated_mouse_motion.delta.x * RADIANS_PER_DOT * controller.sensitivity;
-        transform.rotation = Quat::from_euler(EulerRot::ZYX, 0.0, controller.yaw, controller.pitch);
     }
-}
\ No newline at end of file
+
+    // Handle gamepad look, which does not need the cursor
+    let look = stick_response(
+        Vec2::new(actions.value(Action::LookX), actions.value(Action::LookY)),
+        dead_zone,
+        exponent,
+    );
+    if look != Vec2::ZERO {
+        let look_speed = controller.gamepad_look_speed;
+        controller.pitch = (controller.pitch + look.y * look_speed * dt).clamp(-PI / 2., PI / 2.);
+        controller.yaw -= look.x * look_speed * dt;
+    }
+
+    let (pitch, yaw, rotation_half_life) = (
+        controller.pitch,
+        controller.yaw,
+        controller.rotation_half_life,
+    );
+    smooth_towards(
+        &mut controller.current_pitch,
+        &pitch,
+        rotation_half_life,
+        dt,
+    );
+    smooth_towards(&mut controller.current_yaw, &yaw, rotation_half_life, dt);
+    let rotation = Quat::from_euler(
+        EulerRot::ZYX,
+        0.0,
+        controller.current_yaw,
+        controller.current_pitch,
+    );
+    if transform.rotation != rotation {
+        transform.rotation = rotation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestApp;
+    use std::time::Duration;
+
+    /// Flies forwards and then to the right at `frame_time` per frame, returning where the camera
+    /// comes to rest.
+    fn fly(frame_time: Duration) -> Transform {
+        let mut app = TestApp::looking_at_origin();
+        let camera = app.camera();
+        app.world_mut()
+            .entity_mut(camera)
+            .insert(CameraController::default());
+        app.settings_mut().enabled = false;
+        app.set_frame_time(frame_time);
+
+        for key in [KeyCode::KeyW, KeyCode::KeyD] {
+            app.press_key(key);
+            app.run_for(Duration::from_millis(500));
+            app.release_key(key);
+        }
+        app.run_for(Duration::from_secs(1));
+        app.transform()
+    }
+
+    #[test]
+    fn flying_ends_in_the_same_place_at_any_frame_rate() {
+        let start = TestApp::looking_at_origin().transform();
+        let slow = fly(Duration::from_secs(1) / 30);
+        let fast = fly(Duration::from_secs(1) / 144);
+
+        assert!(slow.translation.distance(start.translation) > 1.0);
+        assert!(
+            slow.translation.abs_diff_eq(fast.translation, 1e-3)
+                && slow.rotation.abs_diff_eq(fast.rotation, 1e-5),
+            "at 30 Hz {slow:?}, at 144 Hz {fast:?}"
+        );
+    }
+
+    #[test]
+    fn gamepad_triggers_fly_up_and_down() {
+        let mut app = TestApp::looking_at_origin();
+        let camera = app.camera();
+        app.world_mut()
+            .entity_mut(camera)
+            .insert(CameraController::default());
+        app.settings_mut().enabled = false;
+        let gamepad = app.connect_gamepad();
+        app.update();
+        let start = app.transform().translation;
+        let speed = CameraController::default().walk_speed;
+
+        app.set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 1.0);
+        app.run_for(Duration::from_millis(500));
+        app.set_gamepad_button(gamepad, GamepadButton::RightTrigger2, 0.0);
+        app.run_for(Duration::from_millis(500));
+        let raised = app.transform().translation;
+        assert!(raised.abs_diff_eq(start + Vec3::Y * speed * 0.5, 1e-3));
+
+        app.set_gamepad_button(gamepad, GamepadButton::LeftTrigger2, 1.0);
+        app.run_for(Duration::from_secs(1));
+        app.set_gamepad_button(gamepad, GamepadButton::LeftTrigger2, 0.0);
+        app.run_for(Duration::from_millis(500));
+        let lowered = app.transform().translation;
+        assert!(lowered.abs_diff_eq(raised - Vec3::Y * speed, 1e-3));
+    }
+}
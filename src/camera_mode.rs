@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+
+use crate::{
+    Ground,
+    camera_controller::CameraController,
+    cursor_grab::CursorGrabRequest,
+    orbit_camera::{CameraSettings, PITCH_LIMIT},
+};
+
+/// Switches cameras carrying both a [`CameraSettings`] and a [`CameraController`] between
+/// orbiting and flying, handing the pose over so the view does not jump.
+pub struct CameraModePlugin;
+
+impl Plugin for CameraModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, switch_camera_mode);
+    }
+}
+
+/// Which controller currently drives the camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// [`CameraSettings`] orbits the camera around its focus point.
+    #[default]
+    Orbit,
+    /// [`CameraController`] flies the camera freely.
+    Fly,
+}
+
+/// Camera mode [`Component`].
+#[derive(Component, Debug)]
+pub struct CameraModeSwitch {
+    /// The mode this camera is in.
+    pub mode: CameraMode,
+    /// [`KeyCode`] for toggling between [`CameraMode::Orbit`] and [`CameraMode::Fly`].
+    pub key_toggle: KeyCode,
+}
+
+impl Default for CameraModeSwitch {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::Orbit,
+            key_toggle: KeyCode::Tab,
+        }
+    }
+}
+
+fn switch_camera_mode(
+    key_input: Res<ButtonInput<KeyCode>>,
+    ground: Single<&GlobalTransform, With<Ground>>,
+    mut cursor_grab_requests: EventWriter<CursorGrabRequest>,
+    mut query: Query<(
+        &mut Transform,
+        &mut CameraModeSwitch,
+        &mut CameraSettings,
+        &mut CameraController,
+    )>,
+) {
+    for (mut transform, mut switch, mut settings, mut controller) in &mut query {
+        if key_input.just_pressed(switch.key_toggle) {
+            switch.mode = match switch.mode {
+                CameraMode::Orbit => {
+                    enter_fly(&settings, &mut controller);
+                    CameraMode::Fly
+                }
+                CameraMode::Fly => {
+                    enter_orbit(&transform, &ground, &controller, &mut settings);
+                    settings.snap();
+                    settings.apply(&mut transform);
+                    // The orbit controls need a visible cursor.
+                    cursor_grab_requests.write(CursorGrabRequest::Release);
+                    CameraMode::Orbit
+                }
+            };
+            info!("Camera mode: {:?}", switch.mode);
+        }
+
+        // Only one controller may write the camera's `Transform` at a time.
+        settings.enabled = switch.mode == CameraMode::Orbit;
+        controller.enabled = switch.mode == CameraMode::Fly;
+    }
+}
+
+/// Seeds the freecam's look angles from the displayed orbit pose.
+fn enter_fly(settings: &CameraSettings, controller: &mut CameraController) {
+    // `CameraController` builds its rotation from yaw around Y followed by pitch around X,
+    // which is the same convention as `OrbitPose::rotation`.
+    controller.yaw = settings.current.yaw;
+    controller.pitch = settings.current.pitch;
+    controller.current_yaw = controller.yaw;
+    controller.current_pitch = controller.pitch;
+    controller.velocity = Vec3::ZERO;
+    controller.initialized = true;
+}
+
+/// Re-derives the orbit pose from where the freecam is looking at the ground.
+fn enter_orbit(
+    transform: &Transform,
+    ground: &GlobalTransform,
+    controller: &CameraController,
+    settings: &mut CameraSettings,
+) {
+    settings.yaw = controller.current_yaw;
+    settings.pitch = controller.current_pitch.clamp(-PITCH_LIMIT, PITCH_LIMIT);
+
+    let ray = Ray3d::new(transform.translation, transform.forward());
+    match ray.intersect_plane(ground.translation(), InfinitePlane3d::new(ground.up())) {
+        Some(distance) => {
+            // Grazing views meet the ground further away than the camera may orbit, and views from
+            // just above it nearer, so focus on the point along the view at the range's limit.
+            let distance = distance.clamp(
+                settings.orbit_distance_range.start,
+                settings.orbit_distance_range.end,
+            );
+            settings.should_focus_at = ray.get_point(distance);
+            settings.orbit_distance = distance;
+        }
+        // Looking away from the ground: keep the current distance and orbit around the point
+        // straight ahead instead.
+        None => {
+            settings.should_focus_at = ray.get_point(settings.orbit_distance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestApp;
+
+    /// Flies the camera to `translation`, looking along `yaw` and `pitch`, and switches back to
+    /// orbiting.
+    fn orbit_from(translation: Vec3, yaw: f32, pitch: f32) -> TestApp {
+        let mut app = TestApp::looking_at_origin();
+        let camera = app.camera();
+        app.world_mut()
+            .entity_mut(camera)
+            .insert((CameraController::default(), CameraModeSwitch::default()));
+        let key = CameraModeSwitch::default().key_toggle;
+        app.press_key(key);
+        app.update();
+        app.release_key(key);
+
+        let mut entity = app.world_mut().entity_mut(camera);
+        let mut controller = entity.get_mut::<CameraController>().unwrap();
+        controller.yaw = yaw;
+        controller.pitch = pitch;
+        controller.current_yaw = yaw;
+        controller.current_pitch = pitch;
+        *entity.get_mut::<Transform>().unwrap() = Transform::from_translation(translation)
+            .with_rotation(Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0));
+        app.update();
+
+        app.press_key(key);
+        app.update();
+        app
+    }
+
+    #[test]
+    fn orbiting_keeps_the_view_where_the_camera_flew() {
+        let start = Vec3::new(3.0, 5.0, 8.0);
+        let app = orbit_from(start, 0.4, -0.6);
+        let settings = app.settings();
+        let ray = Ray3d::new(start, app.transform().forward());
+        let distance = ray
+            .intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))
+            .unwrap();
+
+        assert!((settings.orbit_distance - distance).abs() < 1e-3);
+        assert!(settings.should_focus_at.y.abs() < 1e-3);
+        assert!(app.transform().translation.abs_diff_eq(start, 1e-3));
+    }
+
+    #[test]
+    fn grazing_view_orbits_no_further_than_allowed() {
+        let start = Vec3::new(0.0, 10.0, 0.0);
+        let app = orbit_from(start, 0.0, -0.05);
+        let settings = app.settings();
+        let range = settings.orbit_distance_range.clone();
+
+        assert_eq!(settings.orbit_distance, range.end);
+        let forward = app.transform().forward();
+        assert!(
+            settings
+                .should_focus_at
+                .abs_diff_eq(start + forward * range.end, 1e-3)
+        );
+        assert!(app.transform().translation.abs_diff_eq(start, 1e-3));
+    }
+
+    #[test]
+    fn view_from_just_above_the_ground_orbits_no_nearer_than_allowed() {
+        let start = Vec3::new(0.0, 0.2, 0.0);
+        let app = orbit_from(start, 0.0, -1.0);
+        let settings = app.settings();
+
+        assert_eq!(settings.orbit_distance, settings.orbit_distance_range.start);
+        assert!(app.transform().translation.abs_diff_eq(start, 1e-3));
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+
+/// A cursor-grab service shared by every controller.
+///
+/// Controllers send [`CursorGrabRequest`]s instead of touching the [`Window`] themselves, and
+/// read [`CursorGrab`] to find out whether the cursor is currently grabbed.
+pub struct CursorGrabPlugin;
+
+impl Plugin for CursorGrabPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorGrab>()
+            .add_event::<CursorGrabRequest>()
+            // Requests sent during `Update` take effect in the same frame.
+            .add_systems(PostUpdate, grab_mouse);
+    }
+}
+
+/// Asks the [`CursorGrabPlugin`] to change the cursor grab.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorGrabRequest {
+    Grab,
+    Release,
+    Toggle,
+}
+
+/// Cursor grab [`Resource`].
+#[derive(Resource, Debug)]
+pub struct CursorGrab {
+    /// [`KeyCode`] that always releases the cursor.
+    pub key_release: KeyCode,
+    requested: bool,
+    grabbed: bool,
+}
+
+impl Default for CursorGrab {
+    fn default() -> Self {
+        Self {
+            key_release: KeyCode::Escape,
+            requested: false,
+            grabbed: false,
+        }
+    }
+}
+
+impl CursorGrab {
+    /// Whether a controller wants the cursor grabbed, regardless of window focus.
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// Whether the cursor is grabbed right now.
+    ///
+    /// This is `false` while the window is unfocused, even if a grab
+    /// [is requested](CursorGrab::is_requested); the grab is restored on refocus.
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed
+    }
+}
+
+// This system grabs the mouse when a controller asks for it
+// and releases it when the escape key is pressed or the window loses focus
+fn grab_mouse(
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    mut cursor_grab: ResMut<CursorGrab>,
+    mut requests: EventReader<CursorGrabRequest>,
+    key_input: Res<ButtonInput<KeyCode>>,
+) {
+    for request in requests.read() {
+        cursor_grab.requested = match request {
+            CursorGrabRequest::Grab => true,
+            CursorGrabRequest::Release => false,
+            CursorGrabRequest::Toggle => !cursor_grab.requested,
+        };
+    }
+    if key_input.just_pressed(cursor_grab.key_release) {
+        cursor_grab.requested = false;
+    }
+
+    let grabbed = cursor_grab.requested && window.focused;
+    if grabbed == cursor_grab.grabbed {
+        return;
+    }
+    cursor_grab.grabbed = grabbed;
+
+    if grabbed {
+        window.cursor_options.grab_mode = CursorGrabMode::Locked;
+        window.cursor_options.visible = false;
+    } else {
+        window.cursor_options.grab_mode = CursorGrabMode::None;
+        window.cursor_options.visible = true;
+    }
+}
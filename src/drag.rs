@@ -0,0 +1,211 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{
+    input_map::{Action, ActionInput},
+    orbit_camera::plane_point,
+    physics::{Pose, RigidBody, Spring},
+    picking::Hover,
+    transform_gizmo::TransformGizmo,
+};
+
+/// Drags dynamic bodies around with the cursor on a [`Spring`].
+///
+/// A body is picked up where it is clicked and dragged across the plane through that point
+/// facing the camera.
+pub struct DragPlugin;
+
+impl Plugin for DragPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Drag>()
+            // Picking up happens before `Update` so the orbit camera already knows not to pan
+            // when it sees the same click.
+            .add_systems(PreUpdate, start_drag.after(InputSystem))
+            .add_systems(Update, drag);
+    }
+}
+
+/// Dragging [`Resource`].
+#[derive(Resource, Debug, Default)]
+pub struct Drag {
+    held: Option<Held>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Held {
+    body: Entity,
+    camera: Entity,
+    /// A point on the plane the body is dragged across.
+    origin: Vec3,
+    plane: InfinitePlane3d,
+}
+
+impl Drag {
+    /// Whether a body is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// The body being dragged.
+    pub fn body(&self) -> Option<Entity> {
+        self.held.map(|held| held.body)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_drag(
+    mut commands: Commands,
+    mut drag: ResMut<Drag>,
+    hover: Res<Hover>,
+    gizmo: Res<TransformGizmo>,
+    actions: ActionInput,
+    bodies: Query<(&RigidBody, &Transform)>,
+    parents: Query<&ChildOf>,
+    cameras: Query<&GlobalTransform, With<Camera>>,
+) {
+    // A click on a gizmo handle moves the selection instead.
+    if drag.is_dragging() || gizmo.is_hovered() || !actions.just_pressed(Action::Select) {
+        return;
+    }
+    let (Some(hit), Some(camera)) = (hover.hit(), hover.camera()) else {
+        return;
+    };
+    let Ok(camera_transform) = cameras.get(camera) else {
+        return;
+    };
+
+    // The cursor may be over one of the meshes a body is made of, rather than the body itself.
+    let Some((body, transform)) = std::iter::once(hit.entity)
+        .chain(parents.iter_ancestors(hit.entity))
+        .find_map(|entity| {
+            bodies
+                .get(entity)
+                .ok()
+                .filter(|(body, _)| body.is_dynamic())
+                .map(|(_, transform)| (entity, transform))
+        })
+    else {
+        return;
+    };
+
+    let anchor = Pose::from(transform).inverse_transform_point(hit.point);
+    commands.entity(body).insert(Spring::new(anchor, hit.point));
+    drag.held = Some(Held {
+        body,
+        camera,
+        origin: hit.point,
+        plane: InfinitePlane3d::new(camera_transform.back()),
+    });
+}
+
+// This system moves the spring of the dragged body to the point under the cursor, and lets go
+// of it once the button is released.
+fn drag(
+    mut commands: Commands,
+    mut drag: ResMut<Drag>,
+    actions: ActionInput,
+    window: Single<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut springs: Query<(&Transform, &mut Spring)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(held) = drag.held else {
+        return;
+    };
+    let Ok((transform, mut spring)) = springs.get_mut(held.body) else {
+        // The body was despawned, or its spring removed, while it was held.
+        drag.held = None;
+        return;
+    };
+    if !actions.pressed(Action::Select) {
+        commands.entity(held.body).remove::<Spring>();
+        drag.held = None;
+        return;
+    }
+
+    let point = window
+        .cursor_position()
+        .zip(cameras.get(held.camera).ok())
+        .and_then(|(cursor_position, (camera, camera_transform))| {
+            plane_point(
+                camera,
+                camera_transform,
+                held.origin,
+                held.plane,
+                cursor_position,
+            )
+        });
+    if let Some(point) = point {
+        spring.target = point;
+    }
+
+    gizmos.line(spring.anchor_point(transform), spring.target, Color::WHITE);
+    gizmos.sphere(
+        Isometry3d::from_translation(spring.target),
+        0.1,
+        Color::WHITE,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        physics::{Collider, Gravity, Mass},
+        test_harness::{TestApp, WINDOW_SIZE},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn dragged_point_of_a_scaled_body_follows_the_cursor() {
+        let mut app = TestApp::looking_at_origin();
+        app.world_mut().resource_mut::<Gravity>().0 = Vec3::ZERO;
+        let half_extents = Vec3::new(1.0, 0.5, 1.5);
+        // The collider and the spring ignore the scale, which only affects how it looks. The
+        // body is too heavy to turn, so the clicked point moves exactly as far as the body does.
+        let body = app
+            .world_mut()
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::cuboid(half_extents),
+                Mass {
+                    mass: 1.0,
+                    inertia: Mat3::from_diagonal(Vec3::splat(1e9)),
+                },
+                Transform::from_xyz(0.3, 0.0, 0.2)
+                    .with_rotation(Quat::from_rotation_y(0.5))
+                    .with_scale(Vec3::splat(2.0)),
+            ))
+            .id();
+        app.update();
+        let start = app.world().get::<Transform>(body).unwrap().translation;
+
+        app.press_mouse(MouseButton::Left);
+        app.update();
+        let hit = app.world().resource::<Hover>().hit().unwrap();
+        assert_eq!(hit.entity, body);
+        assert_eq!(app.world().resource::<Drag>().body(), Some(body));
+        let spring = *app.world().get::<Spring>(body).unwrap();
+        let anchor = spring.anchor_point(app.world().get::<Transform>(body).unwrap());
+        assert!(
+            anchor.abs_diff_eq(hit.point, 1e-4),
+            "anchored at {anchor}, clicked {}",
+            hit.point
+        );
+
+        let cursor = WINDOW_SIZE / 2.0 + Vec2::new(120.0, -60.0);
+        app.set_cursor(Some(cursor));
+        app.run_for(Duration::from_secs(3));
+
+        let ray = app.cursor_ray(cursor).unwrap();
+        let facing_camera = InfinitePlane3d::new(app.transform().back());
+        let target = ray.get_point(ray.intersect_plane(hit.point, facing_camera).unwrap());
+        let spring = *app.world().get::<Spring>(body).unwrap();
+        assert!(spring.target.abs_diff_eq(target, 1e-3));
+        let moved = app.world().get::<Transform>(body).unwrap().translation - start;
+        let clicked = hit.point + moved;
+        assert!(
+            clicked.distance(target) < 0.01,
+            "the clicked point is at {clicked}, the cursor at {target}"
+        );
+    }
+}
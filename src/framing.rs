@@ -0,0 +1,168 @@
+use bevy::{
+    prelude::*,
+    render::{camera::ScalingMode, primitives::Aabb, view::VisibilitySystems},
+    scene::SceneInstanceReady,
+    transform::TransformSystem,
+};
+
+use crate::orbit_camera::{CameraSettings, is_hovered};
+
+/// Fits [`CameraSettings`] cameras to the bounds of meshes, on demand and whenever a scene
+/// finishes spawning.
+pub struct FramingPlugin;
+
+impl Plugin for FramingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FrameEntities>()
+            .add_observer(frame_spawned_scene)
+            .add_systems(Update, frame_hotkey)
+            // World-space bounds are only known once transforms have been propagated and mesh
+            // bounds calculated.
+            .add_systems(
+                PostUpdate,
+                frame_entities
+                    .after(TransformSystem::TransformPropagate)
+                    .after(VisibilitySystems::CalculateBounds),
+            );
+    }
+}
+
+/// How much room to leave around framed bounds.
+const FRAME_MARGIN: f32 = 1.1;
+
+/// What a [`FrameEntities`] event should fit the camera to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameTarget {
+    /// Every mesh in the hierarchy below (and including) this entity.
+    Hierarchy(Entity),
+    /// Every entity with a [`Mesh3d`].
+    All,
+}
+
+/// Moves `camera`'s focus to the centre of `target` and zooms so that it fills the viewport.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct FrameEntities {
+    pub camera: Entity,
+    pub target: FrameTarget,
+}
+
+/// [`KeyCode`] for framing every mesh in the camera under the cursor.
+pub const KEY_FRAME_ALL: KeyCode = KeyCode::Home;
+
+fn frame_spawned_scene(
+    trigger: Trigger<SceneInstanceReady>,
+    cameras: Query<Entity, With<CameraSettings>>,
+    mut events: EventWriter<FrameEntities>,
+) {
+    for camera in &cameras {
+        events.write(FrameEntities {
+            camera,
+            target: FrameTarget::Hierarchy(trigger.target()),
+        });
+    }
+}
+
+fn frame_hotkey(
+    cameras: Query<(Entity, &Camera, &CameraSettings)>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window>,
+    mut events: EventWriter<FrameEntities>,
+) {
+    if !key_input.just_pressed(KEY_FRAME_ALL) {
+        return;
+    }
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    for (entity, camera, settings) in &cameras {
+        if settings.enabled && is_hovered(camera, cursor_position) {
+            events.write(FrameEntities {
+                camera: entity,
+                target: FrameTarget::All,
+            });
+        }
+    }
+}
+
+fn frame_entities(
+    mut events: EventReader<FrameEntities>,
+    mut cameras: Query<(&mut Projection, &mut CameraSettings)>,
+    meshes: Query<(Entity, &Aabb, &GlobalTransform), With<Mesh3d>>,
+    children: Query<&Children>,
+) {
+    for event in events.read() {
+        let Ok((mut projection, mut settings)) = cameras.get_mut(event.camera) else {
+            continue;
+        };
+
+        let bounds = match event.target {
+            FrameTarget::Hierarchy(root) => world_bounds(
+                std::iter::once(root)
+                    .chain(children.iter_descendants(root))
+                    .filter_map(|entity| meshes.get(entity).ok()),
+            ),
+            FrameTarget::All => world_bounds(meshes.iter()),
+        };
+        let Some((min, max)) = bounds else {
+            continue;
+        };
+
+        // Fitting the bounding sphere rather than the box keeps the result independent of the
+        // direction the camera is looking from.
+        let center = (min + max) / 2.0;
+        let radius = (max - min).length() / 2.0 * FRAME_MARGIN;
+        settings.should_focus_at = center;
+
+        match *projection {
+            Projection::Perspective(ref perspective) => {
+                // The sphere has to fit the narrower of the two fields of view.
+                let half_fov_y = perspective.fov / 2.0;
+                let half_fov_x = (half_fov_y.tan() * perspective.aspect_ratio).atan();
+                let distance = radius / half_fov_y.min(half_fov_x).sin();
+                settings.orbit_distance = distance.clamp(
+                    settings.orbit_distance_range.start,
+                    settings.orbit_distance_range.end,
+                );
+            }
+            Projection::Orthographic(ref mut orthographic) => {
+                let viewport_height = match orthographic.scaling_mode {
+                    ScalingMode::FixedVertical { viewport_height } => viewport_height,
+                    _ => settings.orthographic_viewport_height,
+                };
+                let aspect_ratio = orthographic.area.width() / orthographic.area.height();
+                let height = if aspect_ratio.is_finite() && aspect_ratio < 1.0 {
+                    2.0 * radius / aspect_ratio
+                } else {
+                    2.0 * radius
+                };
+                orthographic.scale = (height / viewport_height).clamp(
+                    settings.orthographic_zoom_range.start,
+                    settings.orthographic_zoom_range.end,
+                );
+                // Stay far enough back that nothing is clipped by the near plane.
+                settings.orbit_distance = settings.orbit_distance.max(radius * 2.0);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Returns the world-space minimum and maximum corners enclosing all of the given mesh bounds.
+fn world_bounds<'a>(
+    meshes: impl Iterator<Item = (Entity, &'a Aabb, &'a GlobalTransform)>,
+) -> Option<(Vec3, Vec3)> {
+    meshes
+        .map(|(_, aabb, transform)| {
+            let center = transform.transform_point(aabb.center.into());
+            // Project the rotated and scaled half extents onto the world axes.
+            let matrix = transform.affine().matrix3;
+            let half_extents = Vec3::from(
+                matrix.x_axis.abs() * aabb.half_extents.x
+                    + matrix.y_axis.abs() * aabb.half_extents.y
+                    + matrix.z_axis.abs() * aabb.half_extents.z,
+            );
+            (center - half_extents, center + half_extents)
+        })
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+}
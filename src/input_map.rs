@@ -0,0 +1,521 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::system::SystemParam,
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// An action-based input mapping layer shared by the camera controllers.
+///
+/// Systems ask [`ActionInput`] about [`Action`]s instead of reading keys and buttons directly.
+/// The [`InputMap`] resource starts out with built-in defaults and is replaced by
+/// [`INPUT_MAP_PATH`] once it has loaded.
+pub struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>()
+            .init_asset::<InputMap>()
+            .init_asset_loader::<InputMapLoader>()
+            .add_systems(Startup, load_input_map)
+            .add_systems(PreUpdate, apply_loaded_input_map);
+    }
+}
+
+/// The input map loaded at startup, relative to the `assets` folder.
+pub const INPUT_MAP_PATH: &str = "input_map.input.ron";
+
+/// Something the user can do, independently of how it is bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    /// Rotate the orbit camera around its focus point while held.
+    Orbit,
+    /// Drag the orbit camera's focus point across the ground while held.
+    Pan,
+    /// Zoom in on positive values and out on negative values.
+    Zoom,
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    /// Fly faster while held.
+    Run,
+    /// Grab the cursor while held.
+    GrabCursor,
+    /// Toggle the cursor grab.
+    ToggleCursorGrab,
+    /// Click on the entity under the cursor.
+    Select,
+    /// Analog translation to the right, in `-1.0..=1.0`.
+    MoveX,
+    /// Analog translation upwards, in `-1.0..=1.0`.
+    MoveY,
+    /// Analog translation forwards, in `-1.0..=1.0`.
+    MoveZ,
+    /// Analog look to the right, in `-1.0..=1.0`.
+    LookX,
+    /// Analog look upwards, in `-1.0..=1.0`.
+    LookY,
+    /// Analog zoom in on positive values and out on negative values, in `-1.0..=1.0`.
+    ///
+    /// Unlike [`Action::Zoom`], which is a per-frame amount like a wheel step, this is a rate.
+    ZoomRate,
+}
+
+/// A physical input an [`Action`] can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputSource {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// The vertical mouse wheel, in lines.
+    MouseWheel,
+    GamepadButton(GamepadButton),
+    /// A gamepad axis, counting as pressed once it is pushed more than half-way.
+    GamepadAxis(GamepadAxis),
+}
+
+/// An [`InputSource`], optionally combined with modifier keys that have to be held as well.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub source: InputSource,
+    /// Keys that have to be held for the binding to trigger, e.g. `AltLeft` for Alt+LMB.
+    #[serde(default)]
+    pub modifiers: Vec<KeyCode>,
+    /// Multiplier for the value of analog sources. Negative values invert them.
+    #[serde(default = "Binding::default_scale")]
+    pub scale: f32,
+}
+
+impl Binding {
+    fn default_scale() -> f32 {
+        1.0
+    }
+
+    pub fn new(source: InputSource) -> Self {
+        Self {
+            source,
+            modifiers: Vec::new(),
+            scale: Self::default_scale(),
+        }
+    }
+
+    pub fn key(key: KeyCode) -> Self {
+        Self::new(InputSource::Key(key))
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self::new(InputSource::Mouse(button))
+    }
+
+    pub fn gamepad_button(button: GamepadButton) -> Self {
+        Self::new(InputSource::GamepadButton(button))
+    }
+
+    pub fn gamepad_axis(axis: GamepadAxis) -> Self {
+        Self::new(InputSource::GamepadAxis(axis))
+    }
+
+    pub fn with_modifier(mut self, modifier: KeyCode) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{modifier:?}+")?;
+        }
+        match self.source {
+            InputSource::Key(key) => write!(f, "{key:?}"),
+            InputSource::Mouse(button) => write!(f, "Mouse{button:?}"),
+            InputSource::MouseWheel => write!(f, "Scroll"),
+            InputSource::GamepadButton(button) => write!(f, "{button:?}"),
+            InputSource::GamepadAxis(axis) => write!(f, "{axis:?}"),
+        }
+    }
+}
+
+/// Input map [`Resource`] and [`Asset`], binding each [`Action`] to any number of inputs.
+#[derive(Resource, Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
+pub struct InputMap {
+    pub bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let bindings = [
+            (
+                Action::Orbit,
+                vec![
+                    Binding::mouse(MouseButton::Middle),
+                    Binding::mouse(MouseButton::Left).with_modifier(KeyCode::AltLeft),
+                ],
+            ),
+            (Action::Pan, vec![Binding::mouse(MouseButton::Left)]),
+            (Action::Zoom, vec![Binding::new(InputSource::MouseWheel)]),
+            (Action::MoveForward, vec![Binding::key(KeyCode::KeyW)]),
+            (Action::MoveBack, vec![Binding::key(KeyCode::KeyS)]),
+            (Action::MoveLeft, vec![Binding::key(KeyCode::KeyA)]),
+            (Action::MoveRight, vec![Binding::key(KeyCode::KeyD)]),
+            (Action::MoveUp, vec![Binding::key(KeyCode::KeyE)]),
+            (Action::MoveDown, vec![Binding::key(KeyCode::KeyQ)]),
+            (
+                Action::Run,
+                vec![
+                    Binding::key(KeyCode::ShiftLeft),
+                    Binding::gamepad_button(GamepadButton::LeftThumb),
+                ],
+            ),
+            (Action::GrabCursor, vec![Binding::mouse(MouseButton::Left)]),
+            (Action::ToggleCursorGrab, vec![Binding::key(KeyCode::KeyM)]),
+            (Action::Select, vec![Binding::mouse(MouseButton::Left)]),
+            (
+                Action::MoveX,
+                vec![Binding::gamepad_axis(GamepadAxis::LeftStickX)],
+            ),
+            (
+                Action::MoveY,
+                vec![
+                    Binding::gamepad_button(GamepadButton::RightTrigger2),
+                    Binding::gamepad_button(GamepadButton::LeftTrigger2).with_scale(-1.0),
+                ],
+            ),
+            // The left stick moves the freecam and zooms the orbit camera. The two share it
+            // because `CameraModeSwitch` only ever enables one of them at a time.
+            (
+                Action::MoveZ,
+                vec![Binding::gamepad_axis(GamepadAxis::LeftStickY)],
+            ),
+            (
+                Action::LookX,
+                vec![Binding::gamepad_axis(GamepadAxis::RightStickX)],
+            ),
+            (
+                Action::LookY,
+                vec![Binding::gamepad_axis(GamepadAxis::RightStickY)],
+            ),
+            (
+                Action::ZoomRate,
+                vec![Binding::gamepad_axis(GamepadAxis::LeftStickY)],
+            ),
+        ];
+
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl InputMap {
+    /// The inputs bound to `action`.
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// A human-readable list of the inputs bound to `action`.
+    pub fn describe(&self, action: Action) -> String {
+        let bindings: Vec<_> = self
+            .bindings(action)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        if bindings.is_empty() {
+            "Unbound".to_string()
+        } else {
+            bindings.join(" / ")
+        }
+    }
+
+    fn all_bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings.values().flatten()
+    }
+}
+
+/// Reads [`Action`]s through the current [`InputMap`].
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    pub map: Res<'w, InputMap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    mouse_scroll: Res<'w, AccumulatedMouseScroll>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl ActionInput<'_, '_> {
+    /// Returns `true` while any binding of `action` is held.
+    pub fn pressed(&self, action: Action) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|binding| self.is_active(binding) && self.source_pressed(binding.source))
+    }
+
+    /// Returns `true` on the frame any binding of `action` starts being held.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|binding| self.is_active(binding) && self.source_just_pressed(binding.source))
+    }
+
+    /// Returns `true` on the frame any binding of `action` stops being held.
+    ///
+    /// Modifiers are not checked, so letting go of them first does not swallow the release.
+    pub fn just_released(&self, action: Action) -> bool {
+        self.map
+            .bindings(action)
+            .iter()
+            .any(|binding| self.source_just_released(binding.source))
+    }
+
+    /// The summed, scaled value of all of `action`'s active bindings.
+    ///
+    /// Buttons and keys count as `1.0` while held.
+    pub fn value(&self, action: Action) -> f32 {
+        self.map
+            .bindings(action)
+            .iter()
+            .filter(|binding| self.is_active(binding))
+            .map(|binding| self.source_value(binding.source) * binding.scale)
+            .sum()
+    }
+
+    /// A binding is active when its modifiers are held and no other binding on the same source
+    /// with more of them held takes precedence, so that Alt+LMB does not also trigger LMB.
+    fn is_active(&self, binding: &Binding) -> bool {
+        self.modifiers_pressed(binding)
+            && !self.map.all_bindings().any(|other| {
+                other.source == binding.source
+                    && other.modifiers.len() > binding.modifiers.len()
+                    && binding
+                        .modifiers
+                        .iter()
+                        .all(|key| other.modifiers.contains(key))
+                    && self.modifiers_pressed(other)
+            })
+    }
+
+    fn modifiers_pressed(&self, binding: &Binding) -> bool {
+        self.keys.all_pressed(binding.modifiers.iter().copied())
+    }
+
+    fn source_pressed(&self, source: InputSource) -> bool {
+        match source {
+            InputSource::Key(key) => self.keys.pressed(key),
+            InputSource::Mouse(button) => self.mouse_buttons.pressed(button),
+            InputSource::MouseWheel => self.mouse_scroll.delta.y != 0.0,
+            InputSource::GamepadButton(button) => {
+                self.gamepads.iter().any(|gamepad| gamepad.pressed(button))
+            }
+            InputSource::GamepadAxis(_) => self.source_value(source).abs() > 0.5,
+        }
+    }
+
+    fn source_just_pressed(&self, source: InputSource) -> bool {
+        match source {
+            InputSource::Key(key) => self.keys.just_pressed(key),
+            InputSource::Mouse(button) => self.mouse_buttons.just_pressed(button),
+            InputSource::GamepadButton(button) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(button)),
+            // Analog sources have no edges to detect.
+            InputSource::MouseWheel | InputSource::GamepadAxis(_) => self.source_pressed(source),
+        }
+    }
+
+    fn source_just_released(&self, source: InputSource) -> bool {
+        match source {
+            InputSource::Key(key) => self.keys.just_released(key),
+            InputSource::Mouse(button) => self.mouse_buttons.just_released(button),
+            InputSource::GamepadButton(button) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_released(button)),
+            InputSource::MouseWheel | InputSource::GamepadAxis(_) => false,
+        }
+    }
+
+    fn source_value(&self, source: InputSource) -> f32 {
+        match source {
+            InputSource::MouseWheel => match self.mouse_scroll.unit {
+                MouseScrollUnit::Line => self.mouse_scroll.delta.y,
+                MouseScrollUnit::Pixel => self.mouse_scroll.delta.y / 16.0,
+            },
+            InputSource::GamepadAxis(axis) => self
+                .gamepads
+                .iter()
+                .filter_map(|gamepad| gamepad.get(axis))
+                .sum(),
+            InputSource::GamepadButton(button) => self
+                .gamepads
+                .iter()
+                .filter_map(|gamepad| gamepad.get(button))
+                .sum(),
+            InputSource::Key(_) | InputSource::Mouse(_) => {
+                if self.source_pressed(source) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Applies a radial dead zone and a response curve to a stick's position.
+///
+/// Positions closer to the centre than `dead_zone` are ignored, the rest is rescaled so that it
+/// starts from zero at the edge of the dead zone, and its length is raised to `exponent`; values
+/// above one give finer control near the centre.
+pub fn stick_response(input: Vec2, dead_zone: f32, exponent: f32) -> Vec2 {
+    let length = input.length();
+    if length <= dead_zone {
+        return Vec2::ZERO;
+    }
+    let rescaled = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    input / length * rescaled.powf(exponent)
+}
+
+/// [`stick_response`] for a single axis, such as a trigger.
+pub fn axis_response(input: f32, dead_zone: f32, exponent: f32) -> f32 {
+    stick_response(Vec2::new(input, 0.0), dead_zone, exponent).x
+}
+
+/// Loads [`InputMap`]s from `.input.ron` files.
+#[derive(Default)]
+struct InputMapLoader;
+
+impl AssetLoader for InputMapLoader {
+    type Asset = InputMap;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<InputMap, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["input.ron"]
+    }
+}
+
+/// Keeps the loaded [`InputMap`] asset alive.
+#[derive(Resource)]
+struct InputMapHandle(Handle<InputMap>);
+
+fn load_input_map(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(InputMapHandle(asset_server.load(INPUT_MAP_PATH)));
+}
+
+fn apply_loaded_input_map(
+    mut events: EventReader<AssetEvent<InputMap>>,
+    handle: Option<Res<InputMapHandle>>,
+    assets: Res<Assets<InputMap>>,
+    mut input_map: ResMut<InputMap>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) {
+            continue;
+        }
+        if let Some(loaded) = assets.get(&handle.0) {
+            *input_map = loaded.clone();
+            info!("Loaded input map from {INPUT_MAP_PATH}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// The actions the left mouse button is bound to in one way or another.
+    const LEFT_MOUSE_ACTIONS: [Action; 4] = [
+        Action::Orbit,
+        Action::Pan,
+        Action::GrabCursor,
+        Action::Select,
+    ];
+
+    /// The map shipped in the `assets` folder, parsed like [`InputMapLoader`] does.
+    fn shipped_map() -> InputMap {
+        ron::de::from_str(include_str!("../assets/input_map.input.ron"))
+            .expect("the shipped input map should parse")
+    }
+
+    /// Which of [`LEFT_MOUSE_ACTIONS`] are pressed under `map` while `keys` and the left mouse
+    /// button are held.
+    fn left_mouse_actions(map: InputMap, keys: &[KeyCode]) -> Vec<Action> {
+        let mut world = World::new();
+        let mut key_input = ButtonInput::<KeyCode>::default();
+        for key in keys {
+            key_input.press(*key);
+        }
+        let mut mouse_input = ButtonInput::<MouseButton>::default();
+        mouse_input.press(MouseButton::Left);
+        world.insert_resource(map);
+        world.insert_resource(key_input);
+        world.insert_resource(mouse_input);
+        world.init_resource::<AccumulatedMouseScroll>();
+
+        world
+            .run_system_once(|actions: ActionInput| {
+                LEFT_MOUSE_ACTIONS
+                    .into_iter()
+                    .filter(|action| actions.pressed(*action))
+                    .collect()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn shipped_map_matches_the_defaults() {
+        assert_eq!(shipped_map().bindings, InputMap::default().bindings);
+    }
+
+    #[test]
+    fn left_mouse_pans_grabs_and_selects() {
+        assert_eq!(
+            left_mouse_actions(shipped_map(), &[]),
+            [Action::Pan, Action::GrabCursor, Action::Select]
+        );
+    }
+
+    #[test]
+    fn alt_left_mouse_only_orbits() {
+        assert_eq!(
+            left_mouse_actions(shipped_map(), &[KeyCode::AltLeft]),
+            [Action::Orbit]
+        );
+    }
+
+    #[test]
+    fn other_modifiers_do_not_take_precedence() {
+        assert_eq!(
+            left_mouse_actions(shipped_map(), &[KeyCode::ControlLeft]),
+            [Action::Pan, Action::GrabCursor, Action::Select]
+        );
+    }
+}
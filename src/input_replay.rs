@@ -0,0 +1,463 @@
+use bevy::{
+    input::{
+        InputSystem,
+        gamepad::{GamepadInput, gamepad_event_processing_system},
+        keyboard::keyboard_input_system,
+        mouse::{
+            AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit,
+            accumulate_mouse_motion_system, accumulate_mouse_scroll_system,
+            mouse_button_input_system,
+        },
+    },
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+    window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt,
+    hash::Hash,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Records input to a file with an [`InputRecorder`], and plays it back with an
+/// [`InputReplay`], checking that the camera ends up where it did when recording.
+///
+/// Replayed input replaces the real input inside [`InputSystem`], so every system that runs
+/// after it sees the recording.
+pub struct InputReplayPlugin;
+
+impl Plugin for InputReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            First,
+            set_replay_time
+                .before(TimeSystem)
+                .run_if(resource_exists::<InputReplay>),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                replay_input.run_if(resource_exists::<InputReplay>),
+                record_input.run_if(resource_exists::<InputRecorder>),
+            )
+                .chain()
+                .in_set(InputSystem)
+                .after(keyboard_input_system)
+                .after(mouse_button_input_system)
+                .after(accumulate_mouse_motion_system)
+                .after(accumulate_mouse_scroll_system)
+                .after(gamepad_event_processing_system),
+        )
+        .add_systems(
+            Last,
+            (
+                (check_replayed_camera, finish_replay)
+                    .chain()
+                    .run_if(resource_exists::<InputReplay>),
+                (record_camera, save_recording_on_exit)
+                    .chain()
+                    .run_if(resource_exists::<InputRecorder>),
+            ),
+        );
+    }
+}
+
+/// The input of one frame.
+///
+/// Input is recorded per frame rather than per fixed tick, because the camera runs in `Update`.
+/// Replaying the frame durations makes the fixed ticks fall in the same frames as well.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// How long the frame took.
+    pub delta: Duration,
+    /// Keys held down during the frame.
+    pub keys: Vec<KeyCode>,
+    /// Mouse buttons held down during the frame.
+    pub mouse_buttons: Vec<MouseButton>,
+    pub mouse_motion: Vec2,
+    pub scroll_unit: MouseScrollUnit,
+    pub mouse_scroll: Vec2,
+    /// Position of the cursor in the primary window, in logical pixels.
+    pub cursor_position: Option<Vec2>,
+    /// The connected gamepads, in the order they were first seen.
+    #[serde(default)]
+    pub gamepads: Vec<RecordedGamepad>,
+    /// Where the first camera was at the end of the frame.
+    pub camera: Option<Transform>,
+}
+
+/// The input of one gamepad during a [`RecordedFrame`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedGamepad {
+    /// Buttons held down during the frame.
+    pub buttons: Vec<GamepadButton>,
+    /// How far each button was pushed, for the ones that were.
+    pub button_values: Vec<(GamepadButton, f32)>,
+    /// Position of each axis, for the ones away from the centre.
+    pub axes: Vec<(GamepadAxis, f32)>,
+}
+
+impl RecordedGamepad {
+    fn new(gamepad: &Gamepad) -> Self {
+        let mut recorded = Self {
+            buttons: gamepad.get_pressed().copied().collect(),
+            ..default()
+        };
+        for (&input, value) in gamepad.analog().all_axes_and_values() {
+            if value == 0.0 {
+                continue;
+            }
+            match input {
+                GamepadInput::Button(button) => recorded.button_values.push((button, value)),
+                GamepadInput::Axis(axis) => recorded.axes.push((axis, value)),
+            }
+        }
+        recorded
+    }
+}
+
+/// A sequence of [`RecordedFrame`]s, starting from the first frame of the app.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&text)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// Input recording [`Resource`].
+///
+/// Recording starts on the first frame the resource exists, and the recording is saved to
+/// `path` when the app exits. Insert it before the app runs, so the recording can be replayed
+/// from the same starting point.
+#[derive(Resource, Debug)]
+pub struct InputRecorder {
+    path: PathBuf,
+    recording: InputRecording,
+    /// The gamepads seen so far, in the order they are recorded in.
+    gamepads: Vec<Entity>,
+}
+
+impl InputRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            recording: InputRecording::default(),
+            gamepads: Vec::new(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.recording.save(&self.path)
+    }
+
+    /// Stops recording, returning what was recorded.
+    pub fn into_recording(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// Input replay [`Resource`].
+///
+/// While it exists, the recorded input and frame durations replace the real ones. Recorded
+/// gamepads are played back on gamepads of their own, and real gamepads are ignored. Once the
+/// recording runs out the app exits, with an error if the camera did not follow the recorded
+/// path.
+#[derive(Resource, Debug)]
+pub struct InputReplay {
+    recording: InputRecording,
+    frame: usize,
+    mismatch: Option<ReplayMismatch>,
+    /// The gamepads spawned to play back the recorded ones, in the same order.
+    gamepads: Vec<Entity>,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+            mismatch: None,
+            gamepads: Vec::new(),
+        }
+    }
+
+    /// Whether every recorded frame has been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.frames.len()
+    }
+
+    /// The first frame the camera was not where it was when recording, if any.
+    pub fn mismatch(&self) -> Option<&ReplayMismatch> {
+        self.mismatch.as_ref()
+    }
+
+    fn current_frame(&self) -> Option<&RecordedFrame> {
+        self.recording.frames.get(self.frame)
+    }
+}
+
+/// The camera ended a replayed frame somewhere other than where it was when recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayMismatch {
+    pub frame: usize,
+    pub expected: Option<Transform>,
+    pub actual: Option<Transform>,
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "camera diverged on frame {}: expected {:?}, got {:?}",
+            self.frame, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for ReplayMismatch {}
+
+/// How far apart the replayed and recorded camera can be and still match.
+const CAMERA_TOLERANCE: f32 = 1e-4;
+
+fn cameras_match(a: Option<Transform>, b: Option<Transform>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            a.translation.abs_diff_eq(b.translation, CAMERA_TOLERANCE)
+                && a.rotation.abs_diff_eq(b.rotation, CAMERA_TOLERANCE)
+                && a.scale.abs_diff_eq(b.scale, CAMERA_TOLERANCE)
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// The transform of the camera that renders first.
+fn first_camera(cameras: &Query<(&Camera, &Transform)>) -> Option<Transform> {
+    cameras
+        .iter()
+        .min_by_key(|(camera, _)| camera.order)
+        .map(|(_, transform)| *transform)
+}
+
+fn set_replay_time(replay: Res<InputReplay>, mut strategy: ResMut<TimeUpdateStrategy>) {
+    *strategy = match replay.current_frame() {
+        Some(frame) => TimeUpdateStrategy::ManualDuration(frame.delta),
+        None => TimeUpdateStrategy::Automatic,
+    };
+}
+
+/// Makes `input` hold exactly `pressed`, with the buttons held in `previous` counting as
+/// already held.
+fn replay_buttons<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    input: &mut ButtonInput<T>,
+    previous: &[T],
+    pressed: &[T],
+) {
+    input.reset_all();
+    for &button in previous {
+        input.press(button);
+        input.clear_just_pressed(button);
+    }
+    for &button in previous {
+        if !pressed.contains(&button) {
+            input.release(button);
+        }
+    }
+    for &button in pressed {
+        input.press(button);
+    }
+}
+
+/// Makes `gamepad` hold exactly what `recorded` held, or nothing, with `previous` counting as
+/// already held.
+fn replay_gamepad(
+    gamepad: &mut Gamepad,
+    previous: Option<&RecordedGamepad>,
+    recorded: Option<&RecordedGamepad>,
+) {
+    replay_buttons(
+        gamepad.digital_mut(),
+        previous.map_or(&[][..], |previous| &previous.buttons),
+        recorded.map_or(&[][..], |recorded| &recorded.buttons),
+    );
+    let analog = gamepad.analog_mut();
+    let inputs: Vec<GamepadInput> = analog.all_axes().copied().collect();
+    for input in inputs {
+        analog.set(input, 0.0);
+    }
+    if let Some(recorded) = recorded {
+        for &(button, value) in &recorded.button_values {
+            analog.set(button, value);
+        }
+        for &(axis, value) in &recorded.axes {
+            analog.set(axis, value);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn replay_input(
+    mut commands: Commands,
+    mut replay: ResMut<InputReplay>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut mouse_motion: ResMut<AccumulatedMouseMotion>,
+    mut mouse_scroll: ResMut<AccumulatedMouseScroll>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut gamepads: Query<(Entity, &mut Gamepad)>,
+) {
+    let replay = replay.as_mut();
+    let Some(frame) = replay.recording.frames.get(replay.frame) else {
+        return;
+    };
+    let previous = replay
+        .frame
+        .checked_sub(1)
+        .and_then(|frame| replay.recording.frames.get(frame));
+
+    replay_buttons(
+        &mut keys,
+        previous.map_or(&[][..], |previous| &previous.keys),
+        &frame.keys,
+    );
+    replay_buttons(
+        &mut mouse_buttons,
+        previous.map_or(&[][..], |previous| &previous.mouse_buttons),
+        &frame.mouse_buttons,
+    );
+    mouse_motion.delta = frame.mouse_motion;
+    mouse_scroll.unit = frame.scroll_unit;
+    mouse_scroll.delta = frame.mouse_scroll;
+    for mut window in &mut windows {
+        window.set_cursor_position(frame.cursor_position);
+    }
+
+    for (entity, mut gamepad) in &mut gamepads {
+        let index = replay
+            .gamepads
+            .iter()
+            .position(|&replayed| replayed == entity);
+        replay_gamepad(
+            &mut gamepad,
+            index.and_then(|index| previous?.gamepads.get(index)),
+            index.and_then(|index| frame.gamepads.get(index)),
+        );
+    }
+    // Gamepads seen for the first time are spawned holding their input, which the camera sees
+    // this frame already since commands are applied at the end of `PreUpdate`.
+    for recorded in &frame.gamepads[replay.gamepads.len().min(frame.gamepads.len())..] {
+        let mut gamepad = Gamepad::default();
+        replay_gamepad(&mut gamepad, None, Some(recorded));
+        replay.gamepads.push(
+            commands
+                .spawn((Name::new("Replayed gamepad"), gamepad))
+                .id(),
+        );
+    }
+}
+
+fn check_replayed_camera(mut replay: ResMut<InputReplay>, cameras: Query<(&Camera, &Transform)>) {
+    let Some(expected) = replay.current_frame().map(|frame| frame.camera) else {
+        return;
+    };
+    let actual = first_camera(&cameras);
+    if replay.mismatch.is_none() && !cameras_match(expected, actual) {
+        let mismatch = ReplayMismatch {
+            frame: replay.frame,
+            expected,
+            actual,
+        };
+        error!("Replay {mismatch}");
+        replay.mismatch = Some(mismatch);
+    }
+    replay.frame += 1;
+}
+
+fn finish_replay(replay: Res<InputReplay>, mut exit: EventWriter<AppExit>) {
+    if replay.is_changed() && replay.is_finished() {
+        if replay.mismatch().is_some() {
+            exit.write(AppExit::error());
+        } else {
+            info!("Replayed {} frames", replay.recording.frames.len());
+            exit.write(AppExit::Success);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    time: Res<Time<Real>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    window: Option<Single<&Window, With<PrimaryWindow>>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+) {
+    let recorder = recorder.as_mut();
+    for (entity, _) in &gamepads {
+        if !recorder.gamepads.contains(&entity) {
+            recorder.gamepads.push(entity);
+        }
+    }
+    // Gamepads that disconnect leave their place empty, so the later ones keep theirs.
+    let recorded_gamepads = recorder
+        .gamepads
+        .iter()
+        .map(|&entity| {
+            gamepads.get(entity).map_or_else(
+                |_| RecordedGamepad::default(),
+                |(_, gamepad)| RecordedGamepad::new(gamepad),
+            )
+        })
+        .collect();
+    recorder.recording.frames.push(RecordedFrame {
+        delta: time.delta(),
+        keys: keys.get_pressed().copied().collect(),
+        mouse_buttons: mouse_buttons.get_pressed().copied().collect(),
+        mouse_motion: mouse_motion.delta,
+        scroll_unit: mouse_scroll.unit,
+        mouse_scroll: mouse_scroll.delta,
+        cursor_position: window.and_then(|window| window.cursor_position()),
+        gamepads: recorded_gamepads,
+        camera: None,
+    });
+}
+
+fn record_camera(mut recorder: ResMut<InputRecorder>, cameras: Query<(&Camera, &Transform)>) {
+    let camera = first_camera(&cameras);
+    if let Some(frame) = recorder.recording.frames.last_mut() {
+        frame.camera = camera;
+    }
+}
+
+fn save_recording_on_exit(recorder: Res<InputRecorder>, mut exit: EventReader<AppExit>) {
+    if exit.read().next().is_none() {
+        return;
+    }
+    match recorder.save() {
+        Ok(()) => info!(
+            "Saved {} frames of input to {}",
+            recorder.recording.frames.len(),
+            recorder.path.display()
+        ),
+        Err(error) => error!(
+            "Could not save input to {}: {error}",
+            recorder.path.display()
+        ),
+    }
+}
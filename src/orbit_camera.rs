@@ -0,0 +1,854 @@
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*};
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI},
+    ops::Range,
+};
+
+use crate::{
+    Ground,
+    drag::Drag,
+    input_map::{Action, ActionInput, axis_response, stick_response},
+    smoothing::smooth_towards,
+    transform_gizmo::TransformGizmo,
+};
+
+/// An orbit/pan/zoom camera controller plugin.
+///
+/// Every camera with a [`CameraSettings`] component orbits around its own focus point, so
+/// several viewports can be driven independently. Mouse input is routed to the camera whose
+/// viewport is under the cursor.
+pub struct OrbitCameraPlugin;
+
+impl Plugin for OrbitCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            ((orbit, gamepad_orbit, zoom, draw_cursor), smooth_orbit).chain(),
+        );
+    }
+}
+
+/// If the pitch was ±¹⁄₂ π, the camera would look straight up or down.
+/// When the user wants to move the camera back to the horizon, which way should the camera face?
+/// The camera has no way of knowing what direction was "forward" before landing in that extreme position,
+/// so the direction picked will for all intents and purposes be arbitrary.
+/// Another issue is that for mathematical reasons, the yaw will effectively be flipped when the pitch is at the extremes.
+/// To not run into these issues, we clamp the pitch to a safe range.
+pub const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+
+/// How the mouse wheel zooms a perspective [`CameraSettings`] camera.
+///
+/// Orthographic cameras always zoom by changing their scale.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ZoomMode {
+    /// Narrow or widen the field of view.
+    #[default]
+    FieldOfView,
+    /// Move the camera along its view ray by changing
+    /// [`orbit_distance`](CameraSettings::orbit_distance).
+    Dolly,
+    /// Like [`ZoomMode::Dolly`], but also move the focus towards the ground point under the
+    /// cursor so that it stays under the pointer.
+    DollyToCursor,
+}
+
+/// A position around a focus point, see [`CameraSettings::current`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OrbitPose {
+    pub should_focus_at: Vec3,
+    pub orbit_distance: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl OrbitPose {
+    /// The rotation described by [`yaw`](OrbitPose::yaw) and [`pitch`](OrbitPose::pitch).
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    /// The camera translation that looks at [`should_focus_at`](OrbitPose::should_focus_at)
+    /// from [`orbit_distance`](OrbitPose::orbit_distance) away.
+    pub fn translation(&self) -> Vec3 {
+        self.should_focus_at - self.rotation() * Vec3::NEG_Z * self.orbit_distance
+    }
+}
+
+/// Orbit camera [`Component`].
+///
+/// Input changes the target pose held in [`should_focus_at`](CameraSettings::should_focus_at),
+/// [`orbit_distance`](CameraSettings::orbit_distance), [`pitch`](CameraSettings::pitch) and
+/// [`yaw`](CameraSettings::yaw); the camera then eases its [`current`](CameraSettings::current)
+/// pose towards it.
+#[derive(Component, Debug)]
+pub struct CameraSettings {
+    /// Enables this camera's orbit controls when `true`.
+    pub enabled: bool,
+    /// Indicates if this camera has been initialized by the [`OrbitCameraPlugin`].
+    pub initialized: bool,
+    pub orbit_distance: f32,
+    pub pitch_speed: f32,
+    pub yaw_speed: f32,
+    /// The point this camera orbits around.
+    pub should_focus_at: Vec3,
+    /// This camera's pitch rotation around [`should_focus_at`](CameraSettings::should_focus_at).
+    pub pitch: f32,
+    /// This camera's yaw rotation around [`should_focus_at`](CameraSettings::should_focus_at).
+    pub yaw: f32,
+    /// The height of the viewport in world units when the orthographic camera's scale is 1
+    pub orthographic_viewport_height: f32,
+    /// Clamp the orthographic camera's scale to this range
+    pub orthographic_zoom_range: Range<f32>,
+    /// Multiply mouse wheel inputs by this factor when using the orthographic camera
+    pub orthographic_zoom_speed: f32,
+    /// Clamp perspective camera's field of view to this range
+    pub perspective_zoom_range: Range<f32>,
+    /// Multiply mouse wheel inputs by this factor when using the perspective camera
+    pub perspective_zoom_speed: f32,
+    /// How the mouse wheel zooms the perspective camera
+    pub zoom_mode: ZoomMode,
+    /// Below this angle in radians between the view direction and the ground, panning drags a
+    /// plane facing the camera instead of the ground
+    pub min_pan_ground_angle: f32,
+    /// Clamp the orbit distance to this range when dollying
+    pub orbit_distance_range: Range<f32>,
+    /// Multiply mouse wheel inputs by this factor when dollying
+    pub dolly_zoom_speed: f32,
+    /// Seconds for the displayed yaw and pitch to cover half the remaining way to the target
+    pub rotation_half_life: f32,
+    /// Seconds for the displayed focus point and distance to cover half the remaining way to the target
+    pub translation_half_life: f32,
+    /// The pose the camera is displayed at.
+    pub current: OrbitPose,
+    /// [`KeyCode`] for switching between perspective and orthographic projection.
+    pub key_toggle_projection: KeyCode,
+    /// Fraction of a gamepad stick's travel that is ignored around its rest position
+    pub stick_dead_zone: f32,
+    /// Exponent applied to gamepad stick deflection; values above one give finer control near the centre
+    pub stick_response_exponent: f32,
+    /// Radians per second to orbit at full [`Action::LookX`] and [`Action::LookY`] deflection
+    pub gamepad_orbit_speed: f32,
+    /// Mouse wheel steps per second to zoom by at full [`Action::ZoomRate`] deflection
+    pub gamepad_zoom_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initialized: false,
+            orbit_distance: 20.0,
+            pitch_speed: 0.01,
+            yaw_speed: 0.01,
+            should_focus_at: Vec3::ZERO,
+            pitch: 0.0,
+            yaw: 0.0,
+            orthographic_viewport_height: 5.,
+            // In orthographic projections, we specify camera scale relative to a default value of 1,
+            // in which one unit in world space corresponds to one pixel.
+            orthographic_zoom_range: 0.1..10.0,
+            // This value was hand-tuned to ensure that zooming in and out feels smooth but not slow.
+            orthographic_zoom_speed: 0.2,
+            // Perspective projections use field of view, expressed in radians. We would
+            // normally not set it to more than π, which represents a 180° FOV.
+            perspective_zoom_range: (PI / 20.)..(PI - 0.2),
+            // Changes in FOV are much more noticeable due to its limited range in radians
+            perspective_zoom_speed: 0.05,
+            zoom_mode: ZoomMode::FieldOfView,
+            // About 10°, where one pixel at the edge of a typical view covers metres of ground.
+            min_pan_ground_angle: 0.17,
+            orbit_distance_range: 1.0..100.0,
+            // Matches the orthographic zoom speed so both projections feel alike.
+            dolly_zoom_speed: 0.2,
+            rotation_half_life: 0.03,
+            translation_half_life: 0.05,
+            current: OrbitPose::default(),
+            key_toggle_projection: KeyCode::KeyO,
+            stick_dead_zone: 0.15,
+            stick_response_exponent: 2.0,
+            gamepad_orbit_speed: PI,
+            gamepad_zoom_speed: 5.0,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// The pose this camera is easing towards.
+    pub fn target(&self) -> OrbitPose {
+        OrbitPose {
+            should_focus_at: self.should_focus_at,
+            orbit_distance: self.orbit_distance,
+            pitch: self.pitch,
+            yaw: self.yaw,
+        }
+    }
+
+    /// Jumps the [`current`](CameraSettings::current) pose straight to the target.
+    pub fn snap(&mut self) {
+        self.current = self.target();
+    }
+
+    /// Advances the [`current`](CameraSettings::current) pose towards the target by `dt` seconds.
+    pub fn smooth(&mut self, dt: f32) {
+        let target = self.target();
+        let current = &mut self.current;
+        smooth_towards(&mut current.yaw, &target.yaw, self.rotation_half_life, dt);
+        smooth_towards(
+            &mut current.pitch,
+            &target.pitch,
+            self.rotation_half_life,
+            dt,
+        );
+        smooth_towards(
+            &mut current.orbit_distance,
+            &target.orbit_distance,
+            self.translation_half_life,
+            dt,
+        );
+        smooth_towards(
+            &mut current.should_focus_at,
+            &target.should_focus_at,
+            self.translation_half_life,
+            dt,
+        );
+    }
+
+    /// Writes the [`current`](CameraSettings::current) pose into `transform`.
+    pub fn apply(&self, transform: &mut Transform) {
+        transform.rotation = self.current.rotation();
+        transform.translation = self.current.translation();
+    }
+}
+
+/// Returns `true` if `cursor_position` lies within `camera`'s viewport.
+pub fn is_hovered(camera: &Camera, cursor_position: Vec2) -> bool {
+    camera
+        .logical_viewport_rect()
+        .is_some_and(|rect| rect.contains(cursor_position))
+}
+
+/// A point held under the cursor while panning, and the plane it is dragged across.
+#[derive(Debug, Clone, Copy)]
+struct PanGrab {
+    point: Vec3,
+    plane: InfinitePlane3d,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_cursor(
+    mut cameras: Query<(Entity, &Camera, &GlobalTransform, &mut CameraSettings)>,
+    ground: Single<&GlobalTransform, With<Ground>>,
+    actions: ActionInput,
+    window: Single<&Window>,
+    drag: Res<Drag>,
+    transform_gizmo: Res<TransformGizmo>,
+    mut grabs: Local<HashMap<Entity, PanGrab>>,
+    mut gizmos: Gizmos,
+) {
+    // The cursor is dragging an object rather than the ground.
+    let dragging_object = drag.is_dragging() || transform_gizmo.is_dragging();
+    // Let go on release even if the cursor has left the window.
+    if !actions.pressed(Action::Pan) || dragging_object {
+        grabs.clear();
+    }
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    for (entity, camera, global_transform, mut camera_settings) in &mut cameras {
+        if !camera_settings.enabled {
+            grabs.remove(&entity);
+            continue;
+        }
+
+        if let Some(grab) = grabs.get(&entity) {
+            // Solve against the pose the camera is easing towards rather than the one it is
+            // drawn at, so that smoothing does not make the grabbed point drift.
+            let target = camera_settings.target();
+            let target_transform = GlobalTransform::from(
+                Transform::from_translation(target.translation()).with_rotation(target.rotation()),
+            );
+            // Moving the focus by the offset between two points on the plane moves the
+            // point under the cursor by exactly that much. If the cursor is off the plane, the
+            // grab waits for it to come back.
+            if let Some(point) = plane_point(
+                camera,
+                &target_transform,
+                grab.point,
+                grab.plane,
+                cursor_position,
+            ) {
+                camera_settings.should_focus_at += grab.point - point;
+            }
+            continue;
+        }
+
+        if !is_hovered(camera, cursor_position) {
+            continue;
+        }
+
+        // Calculate if and where the ray under the cursor is hitting the ground plane.
+        let point = ground_point(camera, global_transform, &ground, cursor_position);
+        if actions.just_pressed(Action::Pan) && !dragging_object {
+            // Close to edge-on, a small cursor movement covers a lot of ground, so drag a plane
+            // facing the camera through the focus point instead.
+            let ground_angle = global_transform
+                .forward()
+                .dot(*ground.up())
+                .abs()
+                .min(1.0)
+                .asin();
+            let grab = point
+                .filter(|_| ground_angle >= camera_settings.min_pan_ground_angle)
+                .map(|point| PanGrab {
+                    point,
+                    plane: InfinitePlane3d::new(ground.up()),
+                })
+                .or_else(|| {
+                    let plane = InfinitePlane3d::new(global_transform.back());
+                    plane_point(
+                        camera,
+                        global_transform,
+                        camera_settings.current.should_focus_at,
+                        plane,
+                        cursor_position,
+                    )
+                    .map(|point| PanGrab { point, plane })
+                });
+            if let Some(grab) = grab {
+                grabs.insert(entity, grab);
+            }
+        } else if let Some(point) = point.filter(|_| !actions.pressed(Action::Pan)) {
+            // Draw a circle just above the ground plane at that position.
+            gizmos.circle(
+                Isometry3d::new(
+                    point + ground.up() * 0.01,
+                    Quat::from_rotation_arc(Vec3::Z, ground.up().as_vec3()),
+                ),
+                0.2,
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+fn orbit(
+    mut cameras: Query<(&Camera, &Transform, &mut CameraSettings)>,
+    actions: ActionInput,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    window: Single<&Window>,
+) {
+    let cursor_position = window.cursor_position();
+
+    for (camera, transform, mut camera_settings) in &mut cameras {
+        if !camera_settings.initialized {
+            let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
+            camera_settings.yaw = yaw;
+            camera_settings.pitch = pitch;
+            camera_settings.orbit_distance = transform
+                .translation
+                .distance(camera_settings.should_focus_at);
+            camera_settings.snap();
+            camera_settings.initialized = true;
+        }
+
+        if !camera_settings.enabled
+            || !actions.pressed(Action::Orbit)
+            || !cursor_position.is_some_and(|position| is_hovered(camera, position))
+        {
+            continue;
+        }
+
+        let delta = mouse_motion.delta;
+
+        let delta_pitch = delta.y * camera_settings.pitch_speed;
+        let delta_yaw = delta.x * camera_settings.yaw_speed;
+
+        camera_settings.pitch =
+            (camera_settings.pitch + delta_pitch).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+        camera_settings.yaw += delta_yaw;
+    }
+}
+
+// Gamepad input is not tied to the cursor, so it drives every enabled orbit camera.
+fn gamepad_orbit(
+    time: Res<Time>,
+    mut cameras: Query<(&mut Projection, &mut CameraSettings)>,
+    actions: ActionInput,
+) {
+    let dt = time.delta_secs();
+    let look_input = Vec2::new(actions.value(Action::LookX), actions.value(Action::LookY));
+    let zoom_input = actions.value(Action::ZoomRate).clamp(-1.0, 1.0);
+    if look_input == Vec2::ZERO && zoom_input == 0.0 {
+        return;
+    }
+
+    for (mut projection, mut camera_settings) in &mut cameras {
+        if !camera_settings.enabled || !camera_settings.initialized {
+            continue;
+        }
+
+        let (dead_zone, exponent) = (
+            camera_settings.stick_dead_zone,
+            camera_settings.stick_response_exponent,
+        );
+        let look = stick_response(look_input, dead_zone, exponent)
+            * camera_settings.gamepad_orbit_speed
+            * dt;
+
+        // Pushing the stick up tilts the camera the same way as moving the mouse up.
+        camera_settings.pitch = (camera_settings.pitch - look.y).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+        camera_settings.yaw += look.x;
+
+        let zoom = axis_response(zoom_input, dead_zone, exponent);
+        if zoom != 0.0 {
+            let zoom_steps = zoom * camera_settings.gamepad_zoom_speed * dt;
+            apply_zoom(zoom_steps, &mut projection, &mut camera_settings);
+        }
+    }
+}
+
+fn zoom(
+    mut cameras: Query<(
+        &Camera,
+        &GlobalTransform,
+        &mut Projection,
+        &mut CameraSettings,
+    )>,
+    ground: Single<&GlobalTransform, With<Ground>>,
+    actions: ActionInput,
+    window: Single<&Window>,
+) {
+    let zoom_input = actions.value(Action::Zoom);
+    if zoom_input == 0.0 {
+        return;
+    }
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    for (camera, global_transform, mut projection, mut camera_settings) in &mut cameras {
+        if !camera_settings.enabled || !is_hovered(camera, cursor_position) {
+            continue;
+        }
+
+        let Some(zoom_factor) = apply_zoom(zoom_input, &mut projection, &mut camera_settings)
+        else {
+            continue;
+        };
+
+        if camera_settings.zoom_mode == ZoomMode::DollyToCursor {
+            // Scaling the camera's offset from the point under the cursor by the same factor as
+            // the zoom keeps that point fixed on screen, for both projections.
+            if let Some(point) = ground_point(camera, global_transform, &ground, cursor_position) {
+                camera_settings.should_focus_at =
+                    point + (camera_settings.should_focus_at - point) * zoom_factor;
+            }
+        }
+    }
+}
+
+/// Zooms `projection` or dollies `camera_settings` by `zoom_input`, positive values zooming in.
+///
+/// Returns the factor the camera's distance to what it is looking at was scaled by, or `None`
+/// if zooming did not move the camera.
+fn apply_zoom(
+    zoom_input: f32,
+    projection: &mut Projection,
+    camera_settings: &mut CameraSettings,
+) -> Option<f32> {
+    // Usually, you won't need to handle both types of projection,
+    // but doing so makes for a more complete example.
+    match *projection {
+        Projection::Orthographic(ref mut orthographic) => {
+            // We want scrolling up to zoom in, decreasing the scale, so we negate the delta.
+            let delta_zoom = -zoom_input * camera_settings.orthographic_zoom_speed;
+            // When changing scales, logarithmic changes are more intuitive.
+            // To get this effect, we add 1 to the delta, so that a delta of 0
+            // results in no multiplicative effect, positive values result in a multiplicative increase,
+            // and negative values result in multiplicative decreases.
+            let multiplicative_zoom = 1. + delta_zoom;
+
+            let old_scale = orthographic.scale;
+            orthographic.scale = (orthographic.scale * multiplicative_zoom).clamp(
+                camera_settings.orthographic_zoom_range.start,
+                camera_settings.orthographic_zoom_range.end,
+            );
+            Some(orthographic.scale / old_scale)
+        }
+        Projection::Perspective(ref mut perspective) => match camera_settings.zoom_mode {
+            ZoomMode::FieldOfView => {
+                // We want scrolling up to zoom in, decreasing the scale, so we negate the delta.
+                let delta_zoom = -zoom_input * camera_settings.perspective_zoom_speed;
+
+                // Adjust the field of view, but keep it within our stated range.
+                perspective.fov = (perspective.fov + delta_zoom).clamp(
+                    camera_settings.perspective_zoom_range.start,
+                    camera_settings.perspective_zoom_range.end,
+                );
+                None
+            }
+            ZoomMode::Dolly | ZoomMode::DollyToCursor => {
+                // Same logarithmic feel as the orthographic scale, applied to the distance.
+                let multiplicative_zoom = 1. - zoom_input * camera_settings.dolly_zoom_speed;
+
+                let old_distance = camera_settings.orbit_distance;
+                camera_settings.orbit_distance = (old_distance * multiplicative_zoom).clamp(
+                    camera_settings.orbit_distance_range.start,
+                    camera_settings.orbit_distance_range.end,
+                );
+                Some(camera_settings.orbit_distance / old_distance)
+            }
+        },
+        _ => None,
+    }
+}
+
+fn smooth_orbit(time: Res<Time>, mut cameras: Query<(&mut Transform, &mut CameraSettings)>) {
+    let dt = time.delta_secs();
+
+    for (mut transform, mut camera_settings) in &mut cameras {
+        if !camera_settings.enabled || !camera_settings.initialized {
+            continue;
+        }
+
+        camera_settings.smooth(dt);
+
+        let mut smoothed = *transform;
+        camera_settings.apply(&mut smoothed);
+        transform.set_if_neq(smoothed);
+    }
+}
+
+/// Returns the point on the ground plane under `viewport_position`, if there is one.
+pub fn ground_point(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    ground: &GlobalTransform,
+    viewport_position: Vec2,
+) -> Option<Vec3> {
+    plane_point(
+        camera,
+        camera_transform,
+        ground.translation(),
+        InfinitePlane3d::new(ground.up()),
+        viewport_position,
+    )
+}
+
+/// Returns the point on the plane through `origin` under `viewport_position`, if there is one.
+pub fn plane_point(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    origin: Vec3,
+    plane: InfinitePlane3d,
+    viewport_position: Vec2,
+) -> Option<Vec3> {
+    let ray = camera
+        .viewport_to_world(camera_transform, viewport_position)
+        .ok()?;
+    let distance = ray.intersect_plane(origin, plane)?;
+    Some(ray.get_point(distance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{input_replay::InputRecorder, test_harness::TestApp};
+    use std::time::Duration;
+
+    #[test]
+    fn orbit_clamps_pitch() {
+        let mut app = TestApp::looking_at_origin();
+
+        // Far enough to pass the limit, without leaving the window.
+        app.drag(MouseButton::Middle, Vec2::new(0.0, 250.0), 10);
+        assert_eq!(app.settings().pitch, PITCH_LIMIT);
+
+        app.drag(MouseButton::Middle, Vec2::new(0.0, -500.0), 10);
+        assert_eq!(app.settings().pitch, -PITCH_LIMIT);
+    }
+
+    #[test]
+    fn orbit_keeps_looking_at_focus() {
+        let mut app = TestApp::looking_at_origin();
+        let distance = app.settings().orbit_distance;
+
+        app.drag(MouseButton::Middle, Vec2::new(300.0, -80.0), 10);
+        app.settle();
+
+        let transform = app.transform();
+        let to_focus = app.settings().should_focus_at - transform.translation;
+        assert!((to_focus.length() - distance).abs() < 1e-3);
+        assert!(transform.forward().dot(to_focus.normalize()) > 0.9999);
+    }
+
+    #[test]
+    fn pan_keeps_ground_point_under_cursor() {
+        let mut app = TestApp::looking_at_origin();
+        let start = app.cursor().unwrap();
+        let grabbed = app.ground_point(start).unwrap();
+
+        app.drag(MouseButton::Left, Vec2::new(200.0, 0.0), 10);
+        app.settle();
+
+        let under_cursor = app.ground_point(app.cursor().unwrap()).unwrap();
+        assert!(
+            under_cursor.abs_diff_eq(grabbed, 1e-3),
+            "grabbed {grabbed}, now under cursor {under_cursor}"
+        );
+    }
+
+    #[test]
+    fn pan_holds_on_while_cursor_is_outside_window() {
+        let mut app = TestApp::looking_at_origin();
+        let start = app.cursor().unwrap();
+        let grabbed = app.ground_point(start).unwrap();
+
+        app.press_mouse(MouseButton::Left);
+        app.update();
+        app.move_mouse(Vec2::new(-150.0, 40.0));
+        app.update();
+        app.set_cursor(None);
+        app.run(5);
+        let end = start + Vec2::new(120.0, 90.0);
+        app.set_cursor(Some(end));
+        app.update();
+        app.release_mouse(MouseButton::Left);
+        app.settle();
+
+        let under_cursor = app.ground_point(end).unwrap();
+        assert!(
+            under_cursor.abs_diff_eq(grabbed, 1e-3),
+            "grabbed {grabbed}, now under cursor {under_cursor}"
+        );
+    }
+
+    #[test]
+    fn pan_at_grazing_angle_drags_plane_facing_camera() {
+        let mut app =
+            TestApp::new(Transform::from_xyz(0.0, 0.5, 10.0).looking_at(Vec3::ZERO, Vec3::Y));
+        let focus = app.settings().should_focus_at;
+
+        app.drag(MouseButton::Left, Vec2::new(100.0, 50.0), 10);
+        app.settle();
+
+        let ray = app.cursor_ray(app.cursor().unwrap()).unwrap();
+        let along = (focus - ray.origin).dot(*ray.direction);
+        assert!(ray.get_point(along).distance(focus) < 1e-3);
+    }
+
+    /// Orbits and zooms in one frame at `frame_time` per frame, returning the pose a moment
+    /// later while it is still easing towards the new target, and once it has settled.
+    fn orbit_and_zoom(frame_time: Duration) -> (Transform, Transform) {
+        let mut app = TestApp::looking_at_origin();
+        app.settings_mut().zoom_mode = ZoomMode::Dolly;
+        app.set_frame_time(frame_time);
+
+        app.press_mouse(MouseButton::Middle);
+        app.update();
+        app.move_mouse(Vec2::new(200.0, -60.0));
+        app.scroll(2.0);
+        app.update();
+        app.release_mouse(MouseButton::Middle);
+        // The target changed at the start of the last frame.
+        app.run_for(Duration::from_secs(1) / 6 - frame_time);
+        let easing = app.transform();
+        app.run_for(Duration::from_secs(2));
+        (easing, app.transform())
+    }
+
+    #[test]
+    fn smoothing_does_not_depend_on_frame_rate() {
+        let (slow_easing, slow_settled) = orbit_and_zoom(Duration::from_secs(1) / 30);
+        let (fast_easing, fast_settled) = orbit_and_zoom(Duration::from_secs(1) / 144);
+
+        assert!(
+            !slow_easing
+                .translation
+                .abs_diff_eq(slow_settled.translation, 1e-2)
+        );
+        for (slow, fast) in [(slow_easing, fast_easing), (slow_settled, fast_settled)] {
+            assert!(
+                slow.translation.abs_diff_eq(fast.translation, 1e-4)
+                    && slow.rotation.abs_diff_eq(fast.rotation, 1e-5),
+                "at 30 Hz {slow:?}, at 144 Hz {fast:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn field_of_view_zoom_is_clamped() {
+        let mut app = TestApp::looking_at_origin();
+        let range = app.settings().perspective_zoom_range.clone();
+        let fov = |app: &TestApp| match app.projection() {
+            Projection::Perspective(perspective) => perspective.fov,
+            projection => panic!("expected a perspective projection, got {projection:?}"),
+        };
+
+        app.scroll(100.0);
+        app.update();
+        assert_eq!(fov(&app), range.start);
+
+        app.scroll(-100.0);
+        app.update();
+        assert_eq!(fov(&app), range.end);
+    }
+
+    #[test]
+    fn orthographic_zoom_is_clamped() {
+        let mut app = TestApp::looking_at_origin();
+        let range = app.settings().orthographic_zoom_range.clone();
+        let scale = |app: &TestApp| match app.projection() {
+            Projection::Orthographic(orthographic) => orthographic.scale,
+            projection => panic!("expected an orthographic projection, got {projection:?}"),
+        };
+
+        let key = app.settings().key_toggle_projection;
+        app.press_key(key);
+        app.update();
+        app.release_key(key);
+        app.update();
+
+        app.scroll(100.0);
+        app.update();
+        assert_eq!(scale(&app), range.start);
+
+        app.scroll(-1000.0);
+        app.update();
+        assert_eq!(scale(&app), range.end);
+    }
+
+    #[test]
+    fn dolly_zoom_is_clamped() {
+        let mut app = TestApp::looking_at_origin();
+        app.settings_mut().zoom_mode = ZoomMode::Dolly;
+        let range = app.settings().orbit_distance_range.clone();
+
+        app.scroll(10.0);
+        app.update();
+        assert_eq!(app.settings().orbit_distance, range.start);
+
+        app.scroll(-1000.0);
+        app.update();
+        assert_eq!(app.settings().orbit_distance, range.end);
+    }
+
+    #[test]
+    fn gamepad_stick_in_dead_zone_does_not_orbit() {
+        let mut app = TestApp::looking_at_origin();
+        let gamepad = app.connect_gamepad();
+        app.update();
+        let before = (app.settings().yaw, app.settings().pitch, app.transform());
+
+        app.set_gamepad_axis(gamepad, GamepadAxis::RightStickX, 0.1);
+        app.set_gamepad_axis(gamepad, GamepadAxis::RightStickY, -0.1);
+        app.run(30);
+
+        assert_eq!(
+            (app.settings().yaw, app.settings().pitch, app.transform()),
+            before
+        );
+    }
+
+    #[test]
+    fn gamepad_stick_orbits_at_configured_rate() {
+        let mut app = TestApp::looking_at_origin();
+        let gamepad = app.connect_gamepad();
+        app.update();
+        let yaw = app.settings().yaw;
+
+        app.set_gamepad_axis(gamepad, GamepadAxis::RightStickX, 1.0);
+        app.run_for(Duration::from_millis(500));
+
+        let expected = yaw + app.settings().gamepad_orbit_speed * 0.5;
+        assert!(
+            (app.settings().yaw - expected).abs() < 1e-4,
+            "yaw {}, expected {expected}",
+            app.settings().yaw
+        );
+    }
+
+    #[test]
+    fn gamepad_zoom_is_clamped() {
+        let mut app = TestApp::looking_at_origin();
+        app.settings_mut().zoom_mode = ZoomMode::Dolly;
+        let range = app.settings().orbit_distance_range.clone();
+        let gamepad = app.connect_gamepad();
+        app.update();
+
+        app.set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, 1.0);
+        app.run_for(Duration::from_secs(5));
+        assert_eq!(app.settings().orbit_distance, range.start);
+
+        app.set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, -1.0);
+        app.run_for(Duration::from_secs(10));
+        assert_eq!(app.settings().orbit_distance, range.end);
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_camera() {
+        let mut recorded = TestApp::looking_at_origin();
+        recorded
+            .world_mut()
+            .insert_resource(InputRecorder::new("unused.input_replay.ron"));
+        recorded.drag(MouseButton::Middle, Vec2::new(150.0, -40.0), 10);
+        recorded.drag(MouseButton::Left, Vec2::new(-60.0, 30.0), 5);
+        recorded.scroll(3.0);
+        recorded.settle();
+        let recording = recorded
+            .world_mut()
+            .remove_resource::<InputRecorder>()
+            .unwrap()
+            .into_recording();
+
+        let mut replayed = TestApp::looking_at_origin();
+        assert_eq!(replayed.replay(recording), Ok(()));
+        assert_eq!(replayed.transform(), recorded.transform());
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_gamepad() {
+        let mut recorded = TestApp::looking_at_origin();
+        recorded
+            .world_mut()
+            .insert_resource(InputRecorder::new("unused.input_replay.ron"));
+        recorded.update();
+        let gamepad = recorded.connect_gamepad();
+        recorded.update();
+        recorded.set_gamepad_axis(gamepad, GamepadAxis::RightStickX, 0.8);
+        recorded.set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, -0.6);
+        recorded.run_for(Duration::from_millis(500));
+        recorded.set_gamepad_axis(gamepad, GamepadAxis::RightStickX, 0.0);
+        recorded.set_gamepad_axis(gamepad, GamepadAxis::LeftStickY, 0.0);
+        recorded.settle();
+        let recording = recorded
+            .world_mut()
+            .remove_resource::<InputRecorder>()
+            .unwrap()
+            .into_recording();
+        assert!(
+            recording
+                .frames
+                .iter()
+                .any(|frame| !frame.gamepads.is_empty())
+        );
+
+        let start = TestApp::looking_at_origin().transform();
+        assert!(
+            !recorded
+                .transform()
+                .translation
+                .abs_diff_eq(start.translation, 1.0)
+        );
+
+        // A gamepad plugged into the replaying app is ignored, like its keyboard and mouse.
+        let mut replayed = TestApp::looking_at_origin();
+        let real = replayed.connect_gamepad();
+        replayed.update();
+        replayed.set_gamepad_axis(real, GamepadAxis::RightStickY, 1.0);
+        assert_eq!(replayed.replay(recording), Ok(()));
+        assert_eq!(replayed.transform(), recorded.transform());
+    }
+}
@@ -0,0 +1,101 @@
+//! A small rigid-body physics engine, in the spirit of the Vortex Engine it is ported from.
+//!
+//! Everything runs in [`FixedUpdate`] as part of the [`SimulationSet`], so the simulation
+//! advances in the same steps regardless of the frame rate.
+
+use bevy::prelude::*;
+
+use crate::simulation::SimulationSet;
+
+pub use body::{ExternalForce, Mass, RigidBody, Velocity};
+pub use broad_phase::BroadPhase;
+pub use collider::{Collider, Pose};
+pub use collision::{CollisionEnded, CollisionStarted, ContactManifold, Contacts};
+pub use joint::{Joint, JointKind, JointMotor};
+pub use mesh_shape::{ConvexHull, TriMesh};
+pub use narrow_phase::ContactPoint;
+pub use solver::{PhysicsMaterial, SolverSettings};
+pub use spring::Spring;
+
+mod body;
+mod broad_phase;
+mod collider;
+mod collision;
+mod integrator;
+mod joint;
+mod mesh_shape;
+mod narrow_phase;
+mod ray_cast;
+mod scene_colliders;
+mod solver;
+mod spring;
+#[cfg(test)]
+mod test_harness;
+
+/// Rigid-body physics plugin.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Gravity>()
+            .init_resource::<BroadPhase>()
+            .init_resource::<Contacts>()
+            .init_resource::<SolverSettings>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_observer(scene_colliders::colliders_from_scene)
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::Detect,
+                    PhysicsSet::IntegrateVelocities,
+                    PhysicsSet::Solve,
+                    PhysicsSet::IntegratePositions,
+                )
+                    .chain()
+                    .in_set(SimulationSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        broad_phase::update_broad_phase,
+                        collision::detect_collisions,
+                    )
+                        .chain()
+                        .in_set(PhysicsSet::Detect),
+                    (integrator::integrate_velocities, spring::apply_springs)
+                        .chain()
+                        .in_set(PhysicsSet::IntegrateVelocities),
+                    solver::solve_constraints.in_set(PhysicsSet::Solve),
+                    integrator::integrate_positions.in_set(PhysicsSet::IntegratePositions),
+                ),
+            );
+    }
+}
+
+/// The stages of a physics step, in the order they run in [`FixedUpdate`].
+///
+/// This is semi-implicit Euler: velocities are integrated first, corrected by the solver, and
+/// only then used to move the bodies.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    /// Find out what is touching what.
+    Detect,
+    /// Apply gravity and external forces to velocities.
+    IntegrateVelocities,
+    /// Correct velocities so contacts and constraints hold.
+    Solve,
+    /// Move bodies by their velocities.
+    IntegratePositions,
+}
+
+/// Acceleration applied to every [`RigidBody::Dynamic`] body, in metres per second squared.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Gravity(pub Vec3);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self(Vec3::NEG_Y * 9.81)
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::simulation::TransformInterpolation;
+
+/// How a body takes part in the simulation.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[require(Transform, Velocity, Mass, TransformInterpolation)]
+pub enum RigidBody {
+    /// Moved by gravity, forces and contacts.
+    #[default]
+    Dynamic,
+    /// Moved only by its [`Velocity`], and pushes dynamic bodies out of the way.
+    Kinematic,
+    /// Never moves.
+    Static,
+}
+
+impl RigidBody {
+    /// Whether forces, gravity and contacts move this body.
+    pub fn is_dynamic(self) -> bool {
+        self == RigidBody::Dynamic
+    }
+}
+
+/// Linear and angular velocity of a [`RigidBody`], both in world space.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Velocity {
+    /// Metres per second.
+    pub linear: Vec3,
+    /// Axis scaled by radians per second.
+    pub angular: Vec3,
+}
+
+impl Velocity {
+    /// The velocity of the point at `offset` from the centre of mass.
+    pub fn at_point(&self, offset: Vec3) -> Vec3 {
+        self.linear + self.angular.cross(offset)
+    }
+}
+
+/// Mass and inertia tensor of a [`RigidBody`].
+///
+/// The inertia tensor is about the centre of mass, in the body's local space.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Mass {
+    /// Kilograms.
+    pub mass: f32,
+    /// Kilogram square metres.
+    pub inertia: Mat3,
+}
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self::sphere(1.0, 0.5)
+    }
+}
+
+impl Mass {
+    /// A solid sphere.
+    pub fn sphere(mass: f32, radius: f32) -> Self {
+        Self {
+            mass,
+            inertia: Mat3::from_diagonal(Vec3::splat(0.4 * mass * radius * radius)),
+        }
+    }
+
+    /// A solid box with the given half extents.
+    pub fn cuboid(mass: f32, half_extents: Vec3) -> Self {
+        let size = half_extents * 2.0;
+        let squared = size * size;
+        Self {
+            mass,
+            inertia: Mat3::from_diagonal(
+                Vec3::new(
+                    squared.y + squared.z,
+                    squared.x + squared.z,
+                    squared.x + squared.y,
+                ) * mass
+                    / 12.0,
+            ),
+        }
+    }
+
+    /// A solid capsule along the local Y axis, `half_height` being half the length of the
+    /// cylinder between the two hemispheres.
+    pub fn capsule(mass: f32, radius: f32, half_height: f32) -> Self {
+        // Split the mass by volume between the cylinder and the two hemispheres.
+        let cylinder_volume = 2.0 * half_height;
+        let sphere_volume = 4.0 / 3.0 * radius;
+        let cylinder_mass = mass * cylinder_volume / (cylinder_volume + sphere_volume);
+        let sphere_mass = mass - cylinder_mass;
+
+        let r2 = radius * radius;
+        let h = 2.0 * half_height;
+        let axial = cylinder_mass * r2 / 2.0 + sphere_mass * 0.4 * r2;
+        let transverse = cylinder_mass * (r2 / 4.0 + h * h / 12.0)
+            + sphere_mass * (0.4 * r2 + half_height * half_height + 0.375 * radius * h);
+        Self {
+            mass,
+            inertia: Mat3::from_diagonal(Vec3::new(transverse, axial, transverse)),
+        }
+    }
+
+    /// `1 / mass`, or zero for massless bodies so they behave as immovable.
+    pub fn inverse_mass(&self) -> f32 {
+        if self.mass > 0.0 {
+            self.mass.recip()
+        } else {
+            0.0
+        }
+    }
+
+    /// The inverse inertia tensor in world space for a body with the given `rotation`.
+    pub fn inverse_inertia(&self, rotation: Quat) -> Mat3 {
+        if self.mass <= 0.0 || self.inertia.determinant() == 0.0 {
+            return Mat3::ZERO;
+        }
+        let rotation = Mat3::from_quat(rotation);
+        rotation * self.inertia.inverse() * rotation.transpose()
+    }
+
+    /// The inertia tensor in world space for a body with the given `rotation`.
+    pub fn world_inertia(&self, rotation: Quat) -> Mat3 {
+        let rotation = Mat3::from_quat(rotation);
+        rotation * self.inertia * rotation.transpose()
+    }
+}
+
+/// A constant force and torque applied to a [`RigidBody::Dynamic`] body every step, in world
+/// space.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct ExternalForce {
+    /// Newtons, applied at the centre of mass.
+    pub force: Vec3,
+    /// Newton metres.
+    pub torque: Vec3,
+}
@@ -0,0 +1,251 @@
+use bevy::{
+    math::bounding::{Aabb3d, IntersectsVolume},
+    prelude::*,
+};
+use std::collections::{BTreeSet, HashMap};
+
+use super::{
+    Collider,
+    collision::{ColliderItem, collider_pose},
+};
+
+/// Sweep-and-prune broad phase over every [`Collider`].
+///
+/// Bounding boxes are only recomputed for colliders that moved or changed since the last physics
+/// step. The boxes are kept sorted along the X axis, and since bodies move little between
+/// steps the order barely changes, so an insertion sort restores it in close to linear time.
+#[derive(Resource, Debug, Default)]
+pub struct BroadPhase {
+    aabbs: HashMap<Entity, Aabb3d>,
+    /// Entities with bounding boxes, sorted by the low end of their box along X.
+    sorted: Vec<(f32, Entity)>,
+    /// Entities with infinite shapes, which may touch anything, in a fixed order so the pairs
+    /// come out the same on every run.
+    unbounded: BTreeSet<Entity>,
+    pairs: Vec<(Entity, Entity)>,
+}
+
+impl BroadPhase {
+    /// Pairs of colliders whose bounding boxes overlap, as of the last physics step.
+    pub fn pairs(&self) -> &[(Entity, Entity)] {
+        &self.pairs
+    }
+
+    /// Colliders whose bounding boxes overlap `aabb`, including all infinite ones.
+    pub fn query_aabb(&self, aabb: Aabb3d) -> impl Iterator<Item = Entity> + '_ {
+        let start = self
+            .sorted
+            .partition_point(|&(min_x, _)| min_x <= aabb.max.x);
+        self.sorted[..start]
+            .iter()
+            .filter(move |(_, entity)| self.aabbs[entity].intersects(&aabb))
+            .map(|&(_, entity)| entity)
+            .chain(self.unbounded.iter().copied())
+    }
+
+    fn insert(&mut self, entity: Entity, aabb: Option<Aabb3d>) {
+        match aabb {
+            Some(aabb) => {
+                if self.aabbs.insert(entity, aabb).is_none() {
+                    self.sorted.push((aabb.min.x, entity));
+                }
+                self.unbounded.remove(&entity);
+            }
+            None => {
+                self.remove(entity);
+                self.unbounded.insert(entity);
+            }
+        }
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if self.aabbs.remove(&entity).is_some() {
+            self.sorted.retain(|&(_, other)| other != entity);
+        }
+        self.unbounded.remove(&entity);
+    }
+
+    /// Re-sorts the boxes and sweeps along X to find the overlapping pairs.
+    fn update_pairs(&mut self) {
+        for (min_x, entity) in &mut self.sorted {
+            *min_x = self.aabbs[entity].min.x;
+        }
+        insertion_sort(&mut self.sorted);
+
+        self.pairs.clear();
+        let mut active: Vec<Entity> = Vec::new();
+        for &(min_x, entity) in &self.sorted {
+            let aabb = self.aabbs[&entity];
+            // Boxes that end before this one starts cannot touch it or anything after it.
+            active.retain(|other| self.aabbs[other].max.x >= min_x);
+            for &other in &active {
+                if self.aabbs[&other].intersects(&aabb) {
+                    self.pairs.push((other, entity));
+                }
+            }
+            active.push(entity);
+        }
+
+        for &unbounded in &self.unbounded {
+            for &(_, entity) in &self.sorted {
+                self.pairs.push((unbounded, entity));
+            }
+        }
+    }
+}
+
+/// Sorts nearly sorted data in close to linear time.
+fn insertion_sort(entries: &mut [(f32, Entity)]) {
+    for i in 1..entries.len() {
+        let mut j = i;
+        while j > 0 && entries[j - 1].0 > entries[j].0 {
+            entries.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Colliders whose shape or pose may have changed.
+type ColliderChanged = Or<(
+    Changed<Collider>,
+    Changed<Transform>,
+    Changed<GlobalTransform>,
+)>;
+
+pub(super) fn update_broad_phase(
+    mut broad_phase: ResMut<BroadPhase>,
+    changed: Query<ColliderItem, ColliderChanged>,
+    mut removed: RemovedComponents<Collider>,
+) {
+    for entity in removed.read() {
+        broad_phase.remove(entity);
+    }
+    for (entity, collider, global_transform, body) in &changed {
+        let aabb = collider.aabb(&collider_pose(global_transform, body));
+        broad_phase.insert(entity, aabb);
+    }
+
+    broad_phase.update_pairs();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{RigidBody, test_harness::PhysicsTestApp};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use std::{
+        collections::HashSet,
+        time::{Duration, Instant},
+    };
+
+    /// A box of random size somewhere in a small space, so that many of them overlap.
+    fn random_aabb(rng: &mut StdRng) -> Aabb3d {
+        let center = Vec3::new(
+            rng.random_range(-10.0..10.0),
+            rng.random_range(-2.0..2.0),
+            rng.random_range(-10.0..10.0),
+        );
+        let half_size = Vec3::new(
+            rng.random_range(0.1..2.0),
+            rng.random_range(0.1..2.0),
+            rng.random_range(0.1..2.0),
+        );
+        Aabb3d::new(center, half_size)
+    }
+
+    /// The pairs of `broad_phase`, each sorted so that the order within a pair does not matter.
+    fn found_pairs(broad_phase: &BroadPhase) -> HashSet<(Entity, Entity)> {
+        let pairs: HashSet<_> = broad_phase
+            .pairs()
+            .iter()
+            .map(|&(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        assert_eq!(pairs.len(), broad_phase.pairs().len(), "duplicate pairs");
+        pairs
+    }
+
+    /// Every pair of boxes that overlap, and every bounded box with every unbounded one.
+    fn brute_force_pairs(aabbs: &[(Entity, Option<Aabb3d>)]) -> HashSet<(Entity, Entity)> {
+        let mut pairs = HashSet::new();
+        for (i, &(a, aabb_a)) in aabbs.iter().enumerate() {
+            for &(b, aabb_b) in &aabbs[i + 1..] {
+                let overlap = match (aabb_a, aabb_b) {
+                    (Some(aabb_a), Some(aabb_b)) => aabb_a.intersects(&aabb_b),
+                    (None, None) => false,
+                    _ => true,
+                };
+                if overlap {
+                    pairs.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn sweep_and_prune_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut broad_phase = BroadPhase::default();
+        let mut aabbs: Vec<(Entity, Option<Aabb3d>)> = (0..200)
+            .map(|index| {
+                let aabb = (index % 50 != 0).then(|| random_aabb(&mut rng));
+                (Entity::from_raw(index), aabb)
+            })
+            .collect();
+
+        for step in 0..5 {
+            for &(entity, aabb) in &aabbs {
+                broad_phase.insert(entity, aabb);
+            }
+            broad_phase.update_pairs();
+
+            let expected = brute_force_pairs(&aabbs);
+            assert!(!expected.is_empty());
+            assert_eq!(found_pairs(&broad_phase), expected, "step {step}");
+
+            // Move some of the boxes, so the next step has to re-sort.
+            for (_, aabb) in aabbs.iter_mut().step_by(3) {
+                if let Some(aabb) = aabb {
+                    *aabb = random_aabb(&mut rng);
+                }
+            }
+        }
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture broad_phase` to see the timings.
+    #[test]
+    #[ignore = "benchmark"]
+    fn ten_thousand_colliders() {
+        let mut app = PhysicsTestApp::new();
+        for x in 0..100 {
+            for z in 0..100 {
+                app.spawn((
+                    RigidBody::Dynamic,
+                    Collider::sphere(0.4),
+                    Transform::from_xyz(x as f32, (x + z) as f32 * 0.01, z as f32),
+                ));
+            }
+        }
+        // The first step builds the broad phase from scratch.
+        app.step(1);
+
+        let ticks = 60;
+        let start = Instant::now();
+        app.step(ticks);
+        let step_time = start.elapsed() / ticks;
+
+        let pairs = app.world().resource::<BroadPhase>().pairs().len();
+        println!("10k colliders: {step_time:?} per step, {pairs} pairs");
+        assert_eq!(pairs, 0, "the spheres are spread out so none of them touch");
+        // A release build has to fit a step into a 60 Hz frame. Debug builds get some slack.
+        let budget = if cfg!(debug_assertions) {
+            Duration::from_millis(100)
+        } else {
+            Duration::from_millis(16)
+        };
+        assert!(
+            step_time < budget,
+            "{step_time:?} per step, budget {budget:?}"
+        );
+    }
+}
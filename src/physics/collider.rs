@@ -0,0 +1,141 @@
+use bevy::{math::bounding::Aabb3d, prelude::*};
+
+use super::mesh_shape::{ConvexHull, TriMesh};
+
+/// The shape a body collides with, in the entity's local space.
+///
+/// Scale is not applied to colliders; size them in world units instead.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub enum Collider {
+    Sphere {
+        radius: f32,
+    },
+    /// A two-sided, infinitely thin plane through the origin.
+    Plane {
+        normal: Dir3,
+    },
+    /// Everything on the opposite side of `normal` of the plane through the origin.
+    HalfSpace {
+        normal: Dir3,
+    },
+    /// An oriented box centred on the origin.
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// A capsule along the local Y axis, `half_height` being half the length of the segment
+    /// between the centres of its two hemispheres.
+    Capsule {
+        radius: f32,
+        half_height: f32,
+    },
+    ConvexHull(ConvexHull),
+    /// Only collides with solid shapes, and is meant for colliders without a
+    /// [`RigidBody`](super::RigidBody).
+    TriMesh(TriMesh),
+}
+
+impl Collider {
+    pub fn sphere(radius: f32) -> Self {
+        Collider::Sphere { radius }
+    }
+
+    pub fn half_space(normal: Dir3) -> Self {
+        Collider::HalfSpace { normal }
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Collider::Cuboid { half_extents }
+    }
+
+    pub fn capsule(radius: f32, half_height: f32) -> Self {
+        Collider::Capsule {
+            radius,
+            half_height,
+        }
+    }
+
+    /// The convex hull of `points`, or `None` if they enclose no volume.
+    pub fn convex_hull(points: &[Vec3]) -> Option<Self> {
+        ConvexHull::new(points).map(Collider::ConvexHull)
+    }
+
+    pub fn trimesh(vertices: Vec<Vec3>, indices: Vec<[u32; 3]>) -> Self {
+        Collider::TriMesh(TriMesh::new(vertices, indices))
+    }
+
+    /// The world-space bounding box of this collider at `pose`, or `None` for shapes that
+    /// extend infinitely.
+    pub fn aabb(&self, pose: &Pose) -> Option<Aabb3d> {
+        match *self {
+            Collider::Sphere { radius } => Some(Aabb3d::new(pose.translation, Vec3::splat(radius))),
+            Collider::Plane { .. } | Collider::HalfSpace { .. } => None,
+            Collider::Cuboid { half_extents } => {
+                let rotation = Mat3::from_quat(pose.rotation);
+                let extents = rotation.x_axis.abs() * half_extents.x
+                    + rotation.y_axis.abs() * half_extents.y
+                    + rotation.z_axis.abs() * half_extents.z;
+                Some(Aabb3d::new(pose.translation, extents))
+            }
+            Collider::Capsule {
+                radius,
+                half_height,
+            } => {
+                let axis = (pose.rotation * Vec3::Y * half_height).abs();
+                Some(Aabb3d::new(pose.translation, axis + Vec3::splat(radius)))
+            }
+            Collider::ConvexHull(ref hull) => points_aabb(hull.vertices(), pose),
+            Collider::TriMesh(ref mesh) => points_aabb(mesh.vertices(), pose),
+        }
+    }
+}
+
+fn points_aabb(points: &[Vec3], pose: &Pose) -> Option<Aabb3d> {
+    let mut points = points.iter().map(|&point| pose.transform_point(point));
+    let first = points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), point| {
+        (min.min(point), max.max(point))
+    });
+    Some(Aabb3d {
+        min: min.into(),
+        max: max.into(),
+    })
+}
+
+/// The position and orientation of a collider in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl Pose {
+    pub fn new(translation: Vec3, rotation: Quat) -> Self {
+        Self {
+            translation,
+            rotation,
+        }
+    }
+
+    /// Transforms a point from local to world space.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.translation + self.rotation * point
+    }
+
+    /// Transforms a point from world to local space.
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation.inverse() * (point - self.translation)
+    }
+}
+
+impl From<&Transform> for Pose {
+    fn from(transform: &Transform) -> Self {
+        Self::new(transform.translation, transform.rotation)
+    }
+}
+
+impl From<&GlobalTransform> for Pose {
+    fn from(transform: &GlobalTransform) -> Self {
+        let (_scale, rotation, translation) = transform.to_scale_rotation_translation();
+        Self::new(translation, rotation)
+    }
+}
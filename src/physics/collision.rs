@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::{
+    RigidBody,
+    broad_phase::BroadPhase,
+    collider::{Collider, Pose},
+    narrow_phase::{self, ContactPoint},
+};
+
+/// Every pair of colliders touching in the current physics step.
+#[derive(Resource, Debug, Default)]
+pub struct Contacts {
+    pub manifolds: Vec<ContactManifold>,
+    touching: HashSet<(Entity, Entity)>,
+}
+
+impl Contacts {
+    /// Whether `a` and `b` touched in the current physics step.
+    pub fn contains(&self, a: Entity, b: Entity) -> bool {
+        self.touching.contains(&ordered(a, b))
+    }
+}
+
+/// The contact points between two colliders, with normals pointing from `a` to `b`.
+#[derive(Debug, Clone)]
+pub struct ContactManifold {
+    pub a: Entity,
+    pub b: Entity,
+    pub points: Vec<ContactPoint>,
+}
+
+/// Sent when two colliders start touching.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionStarted(pub Entity, pub Entity);
+
+/// Sent when two colliders stop touching.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionEnded(pub Entity, pub Entity);
+
+fn ordered(a: Entity, b: Entity) -> (Entity, Entity) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+/// A collider's shape and world pose, and whether contacts can move it.
+pub(super) type ColliderItem<'a> = (
+    Entity,
+    &'a Collider,
+    &'a GlobalTransform,
+    Option<(&'a RigidBody, &'a Transform)>,
+);
+
+/// The world pose of a collider.
+///
+/// Bodies are moved through their [`Transform`] during the physics step, before
+/// [`GlobalTransform`] is propagated, so their [`Transform`] is the up-to-date one. Bodies are
+/// expected to be root entities for that reason. Colliders without a body never move, so their
+/// [`GlobalTransform`] can be used and they can sit anywhere in a hierarchy.
+pub(super) fn collider_pose(
+    global_transform: &GlobalTransform,
+    body: Option<(&RigidBody, &Transform)>,
+) -> Pose {
+    match body {
+        Some((_, transform)) => Pose::from(transform),
+        None => Pose::from(global_transform),
+    }
+}
+
+/// Whether a pair of colliders needs contacts: at least one of them has to be dynamic.
+pub(super) fn can_collide(
+    a: Option<(&RigidBody, &Transform)>,
+    b: Option<(&RigidBody, &Transform)>,
+) -> bool {
+    let is_dynamic =
+        |body: Option<(&RigidBody, &Transform)>| body.is_some_and(|(body, _)| body.is_dynamic());
+    is_dynamic(a) || is_dynamic(b)
+}
+
+/// Tests the pairs found by the [`BroadPhase`] against each other and records their contacts.
+pub(super) fn detect_collisions(
+    colliders: Query<ColliderItem>,
+    broad_phase: Res<BroadPhase>,
+    mut contacts: ResMut<Contacts>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ended: EventWriter<CollisionEnded>,
+) {
+    let mut manifolds = Vec::new();
+    for &(a, b) in broad_phase.pairs() {
+        let Ok(
+            [
+                (_, collider_a, global_a, body_a),
+                (_, collider_b, global_b, body_b),
+            ],
+        ) = colliders.get_many([a, b])
+        else {
+            continue;
+        };
+        if !can_collide(body_a, body_b) {
+            continue;
+        }
+
+        let points = narrow_phase::contacts(
+            collider_a,
+            &collider_pose(global_a, body_a),
+            collider_b,
+            &collider_pose(global_b, body_b),
+        );
+        if !points.is_empty() {
+            manifolds.push(ContactManifold { a, b, points });
+        }
+    }
+
+    record_contacts(&mut contacts, manifolds, &mut started, &mut ended);
+}
+
+/// Replaces the contacts of the previous step with `manifolds`, sending events for pairs that
+/// started or stopped touching.
+pub(super) fn record_contacts(
+    contacts: &mut Contacts,
+    manifolds: Vec<ContactManifold>,
+    started: &mut EventWriter<CollisionStarted>,
+    ended: &mut EventWriter<CollisionEnded>,
+) {
+    let touching: HashSet<_> = manifolds
+        .iter()
+        .map(|manifold| ordered(manifold.a, manifold.b))
+        .collect();
+
+    for &(a, b) in touching.difference(&contacts.touching) {
+        started.write(CollisionStarted(a, b));
+    }
+    for &(a, b) in contacts.touching.difference(&touching) {
+        ended.write(CollisionEnded(a, b));
+    }
+
+    contacts.manifolds = manifolds;
+    contacts.touching = touching;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Velocity, test_harness::PhysicsTestApp};
+    use bevy::ecs::event::EventCursor;
+
+    #[test]
+    fn landing_and_lifting_off_start_and_end_one_collision() {
+        let mut app = PhysicsTestApp::new();
+        let ground = app.spawn((
+            RigidBody::Static,
+            Collider::half_space(Dir3::Y),
+            Transform::default(),
+        ));
+        let ball = app.spawn((
+            RigidBody::Dynamic,
+            Collider::sphere(0.5),
+            Transform::from_xyz(0.0, 1.0, 0.0),
+        ));
+
+        let mut started_cursor = EventCursor::<CollisionStarted>::default();
+        let mut ended_cursor = EventCursor::<CollisionEnded>::default();
+        let (mut started, mut ended): (Vec<CollisionStarted>, Vec<CollisionEnded>) =
+            (Vec::new(), Vec::new());
+        let mut run = |app: &mut PhysicsTestApp, seconds: f32| {
+            for _ in 0..(seconds / app.timestep()).round() as u32 {
+                app.step(1);
+                let world = app.world();
+                started.extend(
+                    started_cursor
+                        .read(world.resource::<Events<CollisionStarted>>())
+                        .copied(),
+                );
+                ended.extend(
+                    ended_cursor
+                        .read(world.resource::<Events<CollisionEnded>>())
+                        .copied(),
+                );
+            }
+        };
+
+        // Fall half a metre and come to rest on the ground.
+        run(&mut app, 2.0);
+        assert!(app.world().resource::<Contacts>().contains(ground, ball));
+        // Lift the ball well clear of the ground.
+        app.world_mut()
+            .get_mut::<Transform>(ball)
+            .unwrap()
+            .translation
+            .y = 5.0;
+        *app.world_mut().get_mut::<Velocity>(ball).unwrap() = Velocity::default();
+        run(&mut app, 0.2);
+
+        let (a, b) = ordered(ground, ball);
+        assert_eq!(started, [CollisionStarted(a, b)]);
+        assert_eq!(ended, [CollisionEnded(a, b)]);
+        assert!(!app.world().resource::<Contacts>().contains(ground, ball));
+    }
+}
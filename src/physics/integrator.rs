@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use super::{ExternalForce, Gravity, Mass, RigidBody, Velocity};
+
+/// Applies gravity and [`ExternalForce`]s to the velocities of dynamic bodies.
+pub(super) fn integrate_velocities(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    mut bodies: Query<(
+        &RigidBody,
+        &Transform,
+        &mut Velocity,
+        &Mass,
+        Option<&ExternalForce>,
+    )>,
+) {
+    let dt = time.delta_secs();
+
+    for (body, transform, mut velocity, mass, external) in &mut bodies {
+        if !body.is_dynamic() {
+            continue;
+        }
+
+        let external = external.copied().unwrap_or_default();
+        velocity.linear += (gravity.0 + external.force * mass.inverse_mass()) * dt;
+
+        // Euler's rotation equation, including the gyroscopic term that makes spinning bodies
+        // precess.
+        let inertia = mass.world_inertia(transform.rotation);
+        let gyroscopic = velocity.angular.cross(inertia * velocity.angular);
+        velocity.angular +=
+            mass.inverse_inertia(transform.rotation) * (external.torque - gyroscopic) * dt;
+    }
+}
+
+/// Moves dynamic and kinematic bodies by their velocities.
+pub(super) fn integrate_positions(
+    time: Res<Time>,
+    mut bodies: Query<(&RigidBody, &mut Transform, &Velocity)>,
+) {
+    let dt = time.delta_secs();
+
+    for (body, mut transform, velocity) in &mut bodies {
+        if *body == RigidBody::Static {
+            continue;
+        }
+
+        transform.translation += velocity.linear * dt;
+
+        // dq/dt = ½ ω q, renormalised so rounding errors do not build up.
+        let spin = Quat::from_xyzw(
+            velocity.angular.x,
+            velocity.angular.y,
+            velocity.angular.z,
+            0.0,
+        );
+        let rotation = transform.rotation;
+        transform.rotation = (rotation + spin * rotation * (0.5 * dt)).normalize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Collider, test_harness::PhysicsTestApp};
+
+    #[test]
+    fn free_fall_matches_semi_implicit_euler() {
+        let mut app = PhysicsTestApp::new();
+        let gravity = app.world().resource::<Gravity>().0;
+        let start = Vec3::new(1.0, 50.0, -2.0);
+        let body = app.spawn((RigidBody::Dynamic, Transform::from_translation(start)));
+
+        let ticks = 64;
+        app.step(ticks);
+
+        // Each step adds g dt to the velocity before moving by it, so after n steps the body
+        // has moved by g dt² (1 + 2 + ... + n).
+        let dt = app.timestep();
+        let n = ticks as f32;
+        let expected_velocity = gravity * dt * n;
+        let expected_translation = start + gravity * dt * dt * n * (n + 1.0) / 2.0;
+        let (translation, velocity) = (app.transform(body).translation, app.velocity(body));
+        assert!(
+            velocity.linear.abs_diff_eq(expected_velocity, 1e-4),
+            "velocity {}, expected {expected_velocity}",
+            velocity.linear
+        );
+        assert!(
+            translation.abs_diff_eq(expected_translation, 1e-4),
+            "translation {translation}, expected {expected_translation}"
+        );
+    }
+
+    #[test]
+    fn torque_spins_up_through_inertia() {
+        let mut app = PhysicsTestApp::new();
+        let mass = Mass::cuboid(2.0, Vec3::new(0.5, 1.0, 1.5));
+        let torque = Vec3::new(0.0, 0.0, 3.0);
+        let body = app.spawn((
+            RigidBody::Dynamic,
+            mass,
+            ExternalForce {
+                torque,
+                ..default()
+            },
+        ));
+
+        let ticks = 32;
+        app.step(ticks);
+
+        // About a principal axis there is no gyroscopic torque, so ω = τ / I t.
+        let expected = torque / mass.inertia.z_axis.z * app.timestep() * ticks as f32;
+        let angular = app.velocity(body).angular;
+        assert!(
+            angular.abs_diff_eq(expected, 1e-4),
+            "angular velocity {angular}, expected {expected}"
+        );
+        let (axis, angle) = app.transform(body).rotation.to_axis_angle();
+        assert!(angle > 0.0 && axis.abs_diff_eq(Vec3::Z, 1e-4));
+    }
+
+    /// Drops a few spinning boxes and spheres on the ground and returns where they end up.
+    fn tumble() -> Vec<Transform> {
+        let mut app = PhysicsTestApp::new();
+        app.spawn((
+            RigidBody::Static,
+            Collider::half_space(Dir3::Y),
+            Transform::default(),
+        ));
+        let bodies: Vec<Entity> = (0..6)
+            .map(|i| {
+                let i = i as f32;
+                let transform = Transform::from_xyz(i * 0.7 - 2.0, 1.0 + i, i * 0.3)
+                    .with_rotation(Quat::from_euler(EulerRot::YXZ, i, i * 0.5, 0.2));
+                let velocity = Velocity {
+                    linear: Vec3::new(1.0 - i * 0.4, 0.0, 0.5),
+                    angular: Vec3::new(i, 2.0, -i * 0.5),
+                };
+                if i % 2.0 == 0.0 {
+                    let half_extents = Vec3::new(0.4, 0.3, 0.5);
+                    app.spawn((
+                        RigidBody::Dynamic,
+                        Collider::cuboid(half_extents),
+                        Mass::cuboid(1.0, half_extents),
+                        transform,
+                        velocity,
+                    ))
+                } else {
+                    app.spawn((
+                        RigidBody::Dynamic,
+                        Collider::sphere(0.4),
+                        Mass::sphere(1.0, 0.4),
+                        transform,
+                        velocity,
+                    ))
+                }
+            })
+            .collect();
+
+        app.step(128);
+        bodies.iter().map(|&body| app.transform(body)).collect()
+    }
+
+    #[test]
+    fn same_scene_gives_identical_transforms() {
+        let bits = |transforms: Vec<Transform>| -> Vec<u32> {
+            transforms
+                .iter()
+                .flat_map(|transform| {
+                    transform
+                        .translation
+                        .to_array()
+                        .into_iter()
+                        .chain(transform.rotation.to_array())
+                        .chain(transform.scale.to_array())
+                })
+                .map(f32::to_bits)
+                .collect()
+        };
+
+        assert_eq!(bits(tumble()), bits(tumble()));
+    }
+}
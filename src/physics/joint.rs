@@ -0,0 +1,509 @@
+//! Joints connecting pairs of bodies, solved alongside contacts.
+
+use bevy::prelude::*;
+use std::ops::Range;
+
+use super::solver::SolverBody;
+
+/// Connects two bodies so they move together in some ways but not others.
+///
+/// Joints live on their own entities. If `body_a` has no [`RigidBody`](super::RigidBody), the
+/// joint is pinned to the world and `anchor_a` is in world space.
+#[derive(Component, Debug, Clone)]
+pub struct Joint {
+    pub body_a: Entity,
+    pub body_b: Entity,
+    /// Attachment point in `body_a`'s local space.
+    pub anchor_a: Vec3,
+    /// Attachment point in `body_b`'s local space.
+    pub anchor_b: Vec3,
+    pub kind: JointKind,
+    /// `body_b`'s rotation relative to `body_a` the first time the joint was solved. Angular
+    /// constraints, limits and motors are all measured from it.
+    rest_rotation: Option<Quat>,
+}
+
+/// The ways a [`Joint`] can constrain two bodies.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JointKind {
+    /// The anchors stay together; the bodies rotate freely around them.
+    BallSocket,
+    /// The anchors stay together and the bodies only rotate about `axis`, in `body_a`'s local
+    /// space. The limit is in radians and the motor in radians per second.
+    Hinge {
+        axis: Dir3,
+        limit: Option<Range<f32>>,
+        motor: Option<JointMotor>,
+    },
+    /// The bodies keep their relative rotation and `anchor_b` only moves along `axis` through
+    /// `anchor_a`, in `body_a`'s local space. The limit is in metres and the motor in metres per
+    /// second.
+    Slider {
+        axis: Dir3,
+        limit: Option<Range<f32>>,
+        motor: Option<JointMotor>,
+    },
+    /// The bodies move as one.
+    Fixed,
+    /// The distance between the anchors stays within `limit`. An empty range means a rod of
+    /// exactly `limit.start`.
+    Distance { limit: Range<f32> },
+}
+
+/// Drives a [`JointKind::Hinge`] or [`JointKind::Slider`] at a constant speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointMotor {
+    /// Speed along or about the joint axis.
+    pub target_velocity: f32,
+    /// The largest force, or torque for hinges, the motor can apply.
+    pub max_force: f32,
+}
+
+impl Joint {
+    pub fn new(body_a: Entity, body_b: Entity, kind: JointKind) -> Self {
+        Self {
+            body_a,
+            body_b,
+            anchor_a: Vec3::ZERO,
+            anchor_b: Vec3::ZERO,
+            kind,
+            rest_rotation: None,
+        }
+    }
+
+    pub fn ball_socket(body_a: Entity, body_b: Entity) -> Self {
+        Self::new(body_a, body_b, JointKind::BallSocket)
+    }
+
+    pub fn hinge(body_a: Entity, body_b: Entity, axis: Dir3) -> Self {
+        Self::new(
+            body_a,
+            body_b,
+            JointKind::Hinge {
+                axis,
+                limit: None,
+                motor: None,
+            },
+        )
+    }
+
+    pub fn slider(body_a: Entity, body_b: Entity, axis: Dir3) -> Self {
+        Self::new(
+            body_a,
+            body_b,
+            JointKind::Slider {
+                axis,
+                limit: None,
+                motor: None,
+            },
+        )
+    }
+
+    pub fn fixed(body_a: Entity, body_b: Entity) -> Self {
+        Self::new(body_a, body_b, JointKind::Fixed)
+    }
+
+    pub fn distance(body_a: Entity, body_b: Entity, length: f32) -> Self {
+        Self::new(
+            body_a,
+            body_b,
+            JointKind::Distance {
+                limit: length..length,
+            },
+        )
+    }
+
+    pub fn with_anchors(mut self, anchor_a: Vec3, anchor_b: Vec3) -> Self {
+        self.anchor_a = anchor_a;
+        self.anchor_b = anchor_b;
+        self
+    }
+
+    /// Limits the angle of a hinge, the position of a slider or the length of a distance joint.
+    /// Other joints ignore this.
+    pub fn with_limit(mut self, range: Range<f32>) -> Self {
+        match &mut self.kind {
+            JointKind::Hinge { limit, .. } | JointKind::Slider { limit, .. } => {
+                *limit = Some(range)
+            }
+            JointKind::Distance { limit } => *limit = range,
+            JointKind::BallSocket | JointKind::Fixed => (),
+        }
+        self
+    }
+
+    /// Adds a motor to a hinge or slider. Other joints ignore this.
+    pub fn with_motor(mut self, new_motor: JointMotor) -> Self {
+        if let JointKind::Hinge { motor, .. } | JointKind::Slider { motor, .. } = &mut self.kind {
+            *motor = Some(new_motor);
+        }
+        self
+    }
+}
+
+/// One degree of freedom removed by a joint, solved as a single velocity constraint.
+pub(super) struct JointRow {
+    a: usize,
+    b: usize,
+    linear: Vec3,
+    angular_a: Vec3,
+    angular_b: Vec3,
+    mass: f32,
+    target_speed: f32,
+    impulse: f32,
+    min_impulse: f32,
+    max_impulse: f32,
+}
+
+impl JointRow {
+    fn new(
+        bodies: &[SolverBody],
+        (a, b): (usize, usize),
+        linear: Vec3,
+        angular_a: Vec3,
+        angular_b: Vec3,
+    ) -> Self {
+        let (body_a, body_b) = (&bodies[a], &bodies[b]);
+        let k = (body_a.inverse_mass + body_b.inverse_mass) * linear.length_squared()
+            + angular_a.dot(body_a.inverse_inertia * angular_a)
+            + angular_b.dot(body_b.inverse_inertia * angular_b);
+        Self {
+            a,
+            b,
+            linear,
+            angular_a,
+            angular_b,
+            mass: if k > 0.0 { k.recip() } else { 0.0 },
+            target_speed: 0.0,
+            impulse: 0.0,
+            min_impulse: f32::NEG_INFINITY,
+            max_impulse: f32::INFINITY,
+        }
+    }
+
+    /// Keeps a point on `body_b` from moving along `direction` relative to `body_a`.
+    /// `offset_a` is from `body_a`'s centre to where the constraint acts on it.
+    fn point(
+        bodies: &[SolverBody],
+        pair: (usize, usize),
+        offset_a: Vec3,
+        offset_b: Vec3,
+        direction: Vec3,
+    ) -> Self {
+        Self::new(
+            bodies,
+            pair,
+            direction,
+            offset_a.cross(direction),
+            offset_b.cross(direction),
+        )
+    }
+
+    /// Keeps `body_b` from rotating about `axis` relative to `body_a`.
+    fn angular(bodies: &[SolverBody], pair: (usize, usize), axis: Vec3) -> Self {
+        Self::new(bodies, pair, Vec3::ZERO, axis, axis)
+    }
+
+    /// Drives the constraint error back to zero over a few steps.
+    fn correcting(mut self, error: f32, rate: f32) -> Self {
+        self.target_speed = -error * rate;
+        self
+    }
+
+    fn bounded(mut self, min_impulse: f32, max_impulse: f32) -> Self {
+        self.min_impulse = min_impulse;
+        self.max_impulse = max_impulse;
+        self
+    }
+
+    pub(super) fn solve(&mut self, bodies: &mut [SolverBody]) {
+        let (velocity_a, velocity_b) = (bodies[self.a].velocity, bodies[self.b].velocity);
+        let speed = self.linear.dot(velocity_b.linear - velocity_a.linear)
+            + self.angular_b.dot(velocity_b.angular)
+            - self.angular_a.dot(velocity_a.angular);
+
+        let impulse = (self.impulse + (self.target_speed - speed) * self.mass)
+            .clamp(self.min_impulse, self.max_impulse);
+        let delta = impulse - self.impulse;
+        self.impulse = impulse;
+
+        let body_a = &mut bodies[self.a];
+        body_a.velocity.linear -= self.linear * delta * body_a.inverse_mass;
+        body_a.velocity.angular -= body_a.inverse_inertia * self.angular_a * delta;
+        let body_b = &mut bodies[self.b];
+        body_b.velocity.linear += self.linear * delta * body_b.inverse_mass;
+        body_b.velocity.angular += body_b.inverse_inertia * self.angular_b * delta;
+    }
+}
+
+/// A row that pushes `value` back inside `limit` once it has left it.
+fn limit_row(row: JointRow, value: f32, limit: &Range<f32>, rate: f32) -> Option<JointRow> {
+    if limit.start >= limit.end {
+        Some(row.correcting(value - limit.start, rate))
+    } else if value < limit.start {
+        Some(
+            row.correcting(value - limit.start, rate)
+                .bounded(0.0, f32::INFINITY),
+        )
+    } else if value > limit.end {
+        Some(
+            row.correcting(value - limit.end, rate)
+                .bounded(f32::NEG_INFINITY, 0.0),
+        )
+    } else {
+        None
+    }
+}
+
+fn motor_row(row: JointRow, motor: &JointMotor, dt: f32) -> JointRow {
+    let max_impulse = motor.max_force.abs() * dt;
+    JointRow {
+        target_speed: motor.target_velocity,
+        ..row
+    }
+    .bounded(-max_impulse, max_impulse)
+}
+
+/// Builds the rows of `joint` between the solver bodies `a` and `b`.
+///
+/// `rate` is how much of the position error is corrected per second.
+pub(super) fn joint_rows(
+    joint: &mut Joint,
+    (a, b): (usize, usize),
+    bodies: &[SolverBody],
+    rate: f32,
+    dt: f32,
+) -> Vec<JointRow> {
+    let (body_a, body_b) = (&bodies[a], &bodies[b]);
+    let pair = (a, b);
+    let rest_rotation = *joint
+        .rest_rotation
+        .get_or_insert(body_a.rotation.inverse() * body_b.rotation);
+
+    let anchor_a = body_a.center + body_a.rotation * joint.anchor_a;
+    let anchor_b = body_b.center + body_b.rotation * joint.anchor_b;
+    let offset_a = anchor_a - body_a.center;
+    let offset_b = anchor_b - body_b.center;
+    let separation = anchor_b - anchor_a;
+
+    // The world-space rotation from where `body_b` would be at rest to where it is, as an axis
+    // scaled by the angle.
+    let mut deviation = body_b.rotation * (body_a.rotation * rest_rotation).inverse();
+    if deviation.w < 0.0 {
+        deviation = -deviation;
+    }
+    let angle_error = 2.0 * deviation.xyz();
+
+    let point_rows = |offset_a: Vec3, directions: &[Vec3]| {
+        directions
+            .iter()
+            .map(|&direction| {
+                JointRow::point(bodies, pair, offset_a, offset_b, direction)
+                    .correcting(separation.dot(direction), rate)
+            })
+            .collect::<Vec<_>>()
+    };
+    let angular_rows = |axes: &[Vec3]| {
+        axes.iter()
+            .map(|&axis| {
+                JointRow::angular(bodies, pair, axis).correcting(angle_error.dot(axis), rate)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    match &joint.kind {
+        JointKind::BallSocket => point_rows(offset_a, &[Vec3::X, Vec3::Y, Vec3::Z]),
+        JointKind::Fixed => {
+            let mut rows = point_rows(offset_a, &[Vec3::X, Vec3::Y, Vec3::Z]);
+            rows.extend(angular_rows(&[Vec3::X, Vec3::Y, Vec3::Z]));
+            rows
+        }
+        JointKind::Hinge { axis, limit, motor } => {
+            let axis = body_a.rotation * axis.as_vec3();
+            let (tangent, bitangent) = axis.any_orthonormal_pair();
+            let mut rows = point_rows(offset_a, &[Vec3::X, Vec3::Y, Vec3::Z]);
+            rows.extend(angular_rows(&[tangent, bitangent]));
+
+            let angle = 2.0 * deviation.xyz().dot(axis).atan2(deviation.w);
+            let row = || JointRow::angular(bodies, pair, axis);
+            rows.extend(
+                limit
+                    .as_ref()
+                    .and_then(|limit| limit_row(row(), angle, limit, rate)),
+            );
+            rows.extend(motor.as_ref().map(|motor| motor_row(row(), motor, dt)));
+            rows
+        }
+        JointKind::Slider { axis, limit, motor } => {
+            let axis = body_a.rotation * axis.as_vec3();
+            let (tangent, bitangent) = axis.any_orthonormal_pair();
+            // The axis turns with `body_a`, so it acts on `body_a` where `anchor_b` is, rather
+            // than at its own anchor.
+            let slide_offset = anchor_b - body_a.center;
+            let mut rows = angular_rows(&[Vec3::X, Vec3::Y, Vec3::Z]);
+            rows.extend(point_rows(slide_offset, &[tangent, bitangent]));
+
+            let position = separation.dot(axis);
+            let row = || JointRow::point(bodies, pair, slide_offset, offset_b, axis);
+            rows.extend(
+                limit
+                    .as_ref()
+                    .and_then(|limit| limit_row(row(), position, limit, rate)),
+            );
+            rows.extend(motor.as_ref().map(|motor| motor_row(row(), motor, dt)));
+            rows
+        }
+        JointKind::Distance { limit } => {
+            let length = separation.length();
+            if length <= f32::EPSILON {
+                return Vec::new();
+            }
+            let row = JointRow::point(bodies, pair, offset_a, offset_b, separation / length);
+            limit_row(row, length, limit, rate).into_iter().collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Gravity, Mass, RigidBody, Velocity, test_harness::PhysicsTestApp};
+
+    const PIVOT: Vec3 = Vec3::new(1.0, 5.0, -2.0);
+    const ARM: f32 = 2.0;
+
+    /// A bob held level at [`ARM`] from [`PIVOT`] by `joint`, which is given the world and the
+    /// bob, and left to swing down. Returns the bob.
+    fn pendulum(app: &mut PhysicsTestApp, joint: impl FnOnce(Entity, Entity) -> Joint) -> Entity {
+        let world = app.spawn(());
+        let bob = app.spawn((
+            RigidBody::Dynamic,
+            Mass::sphere(1.0, 0.3),
+            Transform::from_translation(PIVOT + Vec3::X * ARM),
+        ));
+        app.spawn(joint(world, bob));
+        bob
+    }
+
+    /// Runs the physics for `seconds`, calling `check` after every step.
+    fn run(app: &mut PhysicsTestApp, seconds: f32, mut check: impl FnMut(&PhysicsTestApp)) {
+        for _ in 0..(seconds / app.timestep()).round() as u32 {
+            app.step(1);
+            check(app);
+        }
+    }
+
+    #[test]
+    fn hinged_pendulum_stays_on_its_pivot() {
+        let mut app = PhysicsTestApp::new();
+        let bob = pendulum(&mut app, |world, bob| {
+            Joint::hinge(world, bob, Dir3::Z).with_anchors(PIVOT, Vec3::NEG_X * ARM)
+        });
+
+        // The solver only corrects a fraction of the drift each step, so the anchor lags behind
+        // the swing a little, but it must not wander off further over time.
+        let mut drift = [0.0_f32; 3];
+        let mut lowest = PIVOT.y;
+        for window in &mut drift {
+            run(&mut app, 10.0, |app| {
+                let transform = app.transform(bob);
+                let anchor = transform.translation + transform.rotation * Vec3::NEG_X * ARM;
+                *window = window.max(anchor.distance(PIVOT));
+                lowest = lowest.min(transform.translation.y);
+            });
+        }
+
+        assert!(
+            drift.iter().all(|&drift| drift < 0.15) && drift[2] <= drift[0],
+            "the anchor drifted up to {drift:?} m from the pivot in each 10 s"
+        );
+        assert!(
+            lowest < PIVOT.y - 0.9 * ARM,
+            "the bob only fell to {lowest}"
+        );
+        let (axis, _) = app.transform(bob).rotation.to_axis_angle();
+        assert!(axis.abs_diff_eq(Vec3::Z, 1e-3) || axis.abs_diff_eq(Vec3::NEG_Z, 1e-3));
+    }
+
+    #[test]
+    fn hinge_angle_stays_inside_its_limit() {
+        let mut app = PhysicsTestApp::new();
+        let limit = -0.5..0.3;
+        let bob = pendulum(&mut app, |world, bob| {
+            Joint::hinge(world, bob, Dir3::Z)
+                .with_anchors(PIVOT, Vec3::NEG_X * ARM)
+                .with_limit(limit.clone())
+        });
+
+        let (mut lowest, mut highest) = (0.0_f32, 0.0_f32);
+        run(&mut app, 5.0, |app| {
+            let angle = app.transform(bob).rotation.to_scaled_axis().z;
+            lowest = lowest.min(angle);
+            highest = highest.max(angle);
+        });
+
+        // Swinging down turns the bob clockwise about Z, onto the lower limit.
+        assert!(
+            (lowest - limit.start).abs() < 0.05,
+            "the angle went down to {lowest}, limit {limit:?}"
+        );
+        assert!(highest < limit.end + 0.05, "the angle went up to {highest}");
+    }
+
+    #[test]
+    fn hinge_motor_reaches_its_target_speed() {
+        let mut app = PhysicsTestApp::new();
+        app.world_mut().resource_mut::<Gravity>().0 = Vec3::ZERO;
+        let world = app.spawn(());
+        let wheel = app.spawn((
+            RigidBody::Dynamic,
+            Mass::cuboid(4.0, Vec3::new(0.5, 0.1, 0.5)),
+            Transform::from_translation(PIVOT),
+        ));
+        let motor = JointMotor {
+            target_velocity: 3.0,
+            max_force: 2.0,
+        };
+        app.spawn(
+            Joint::hinge(world, wheel, Dir3::Y)
+                .with_anchors(PIVOT, Vec3::ZERO)
+                .with_motor(motor),
+        );
+
+        app.step(1);
+        let first = app.velocity(wheel).angular.y;
+        assert!(
+            first < motor.target_velocity / 2.0,
+            "a weak motor should take a while to spin up, but ran at {first} after one step"
+        );
+
+        run(&mut app, 3.0, |_| ());
+        let angular = app.velocity(wheel).angular;
+        assert!(
+            angular.abs_diff_eq(Vec3::Y * motor.target_velocity, 1e-3),
+            "angular velocity {angular}"
+        );
+    }
+
+    #[test]
+    fn distance_joint_holds_its_length() {
+        let mut app = PhysicsTestApp::new();
+        let bob = pendulum(&mut app, |world, bob| {
+            Joint::distance(world, bob, ARM).with_anchors(PIVOT, Vec3::ZERO)
+        });
+        // Send the bob around the pivot as well as down, so the joint pulls in every direction.
+        app.world_mut().get_mut::<Velocity>(bob).unwrap().linear = Vec3::new(0.0, 0.0, 3.0);
+
+        let (mut shortest, mut longest) = (ARM, ARM);
+        run(&mut app, 10.0, |app| {
+            let length = app.transform(bob).translation.distance(PIVOT);
+            shortest = shortest.min(length);
+            longest = longest.max(length);
+        });
+
+        assert!(
+            ARM - shortest < 0.02 && longest - ARM < 0.02,
+            "the length varied between {shortest} and {longest}"
+        );
+    }
+}
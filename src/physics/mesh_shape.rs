@@ -0,0 +1,218 @@
+//! Collider shapes built from arbitrary points and triangles.
+
+use bevy::prelude::*;
+
+/// A convex polyhedron, stored as its corners and the planes of its faces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull {
+    vertices: Vec<Vec3>,
+    /// Outward unit normals of the faces, with each face's distance from the origin along it.
+    planes: Vec<(Vec3, f32)>,
+}
+
+impl ConvexHull {
+    /// The smallest convex shape containing every point, or `None` if the points are all on one
+    /// plane and enclose no volume.
+    pub fn new(points: &[Vec3]) -> Option<Self> {
+        let faces = hull_faces(points)?;
+
+        let mut used = vec![false; points.len()];
+        let mut planes: Vec<(Vec3, f32)> = Vec::new();
+        for [a, b, c] in faces {
+            used[a] = true;
+            used[b] = true;
+            used[c] = true;
+
+            let Some(normal) = (points[b] - points[a])
+                .cross(points[c] - points[a])
+                .try_normalize()
+            else {
+                continue;
+            };
+            let offset = normal.dot(points[a]);
+            // Faces with more than three corners are made of several coplanar triangles.
+            if !planes.iter().any(|&(other_normal, other_offset)| {
+                normal.dot(other_normal) > 0.9999 && (offset - other_offset).abs() < 1e-4
+            }) {
+                planes.push((normal, offset));
+            }
+        }
+
+        let vertices = points
+            .iter()
+            .zip(used)
+            .filter_map(|(&point, used)| used.then_some(point))
+            .collect();
+        Some(Self { vertices, planes })
+    }
+
+    /// The corners of the hull, in local space.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// The face planes of the hull in local space, as outward unit normals and distances from
+    /// the origin.
+    pub fn planes(&self) -> &[(Vec3, f32)] {
+        &self.planes
+    }
+}
+
+/// Incremental convex hull: start with a tetrahedron, then add the points one at a time,
+/// replacing the faces each one can see with a fan of faces from their silhouette to it.
+///
+/// Returns triangles wound counter-clockwise seen from outside.
+fn hull_faces(points: &[Vec3]) -> Option<Vec<[usize; 3]>> {
+    let first = *points.first()?;
+    let extent = points
+        .iter()
+        .map(|point| point.distance(first))
+        .fold(0.0, f32::max);
+    let epsilon = extent * 1e-5;
+
+    // The starting tetrahedron is made of points as far from each other as possible.
+    let farthest = |distance: &dyn Fn(Vec3) -> f32| {
+        (0..points.len()).max_by(|&i, &j| distance(points[i]).total_cmp(&distance(points[j])))
+    };
+    let i0 = 0;
+    let i1 = farthest(&|point| point.distance(first))?;
+    let direction = (points[i1] - first).try_normalize()?;
+    let i2 = farthest(&|point| (point - first).reject_from_normalized(direction).length())?;
+    let plane_normal = (points[i1] - first)
+        .cross(points[i2] - first)
+        .try_normalize()?;
+    let i3 = farthest(&|point| (point - first).dot(plane_normal).abs())?;
+    if (points[i3] - first).dot(plane_normal).abs() <= epsilon {
+        return None;
+    }
+
+    let normal = |[a, b, c]: [usize; 3]| {
+        (points[b] - points[a])
+            .cross(points[c] - points[a])
+            .normalize_or_zero()
+    };
+    let mut faces = vec![[i0, i1, i2], [i0, i2, i3], [i0, i3, i1], [i1, i3, i2]];
+    // Wind the tetrahedron outwards.
+    if (points[i3] - first).dot(normal([i0, i1, i2])) > 0.0 {
+        for face in &mut faces {
+            face.swap(1, 2);
+        }
+    }
+
+    for (i, &point) in points.iter().enumerate() {
+        if [i0, i1, i2, i3].contains(&i) {
+            continue;
+        }
+
+        let (visible, hidden): (Vec<_>, Vec<_>) = faces
+            .into_iter()
+            .partition(|&face| (point - points[face[0]]).dot(normal(face)) > epsilon);
+        faces = hidden;
+        if visible.is_empty() {
+            continue;
+        }
+
+        // The silhouette is made of the edges of visible faces whose neighbours are hidden.
+        let edges: Vec<(usize, usize)> = visible
+            .iter()
+            .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+            .collect();
+        for &(a, b) in &edges {
+            if !edges.contains(&(b, a)) {
+                faces.push([a, b, i]);
+            }
+        }
+    }
+
+    Some(faces)
+}
+
+/// A triangle mesh. It is hollow and one-sided, with triangles facing the side they are wound
+/// counter-clockwise from, which makes it suited to static level geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriMesh {
+    vertices: Vec<Vec3>,
+    indices: Vec<[u32; 3]>,
+}
+
+impl TriMesh {
+    pub fn new(vertices: Vec<Vec3>, indices: Vec<[u32; 3]>) -> Self {
+        Self { vertices, indices }
+    }
+
+    /// The vertices of the mesh, in local space.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// The corners of every triangle, in local space.
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.indices
+            .iter()
+            .map(|indices| indices.map(|index| self.vertices[index as usize]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube_corners() -> Vec<Vec3> {
+        (0..8)
+            .map(|i| {
+                Vec3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cube_hull_has_its_corners_and_six_faces() {
+        let corners = cube_corners();
+        // Points inside and on the faces are not corners of the hull.
+        let mut points = vec![Vec3::ZERO, Vec3::new(0.2, -0.3, 0.5)];
+        points.extend(corners.iter().copied());
+        points.extend([Vec3::X, Vec3::NEG_Y, Vec3::new(0.5, 0.5, 1.0)]);
+
+        let hull = ConvexHull::new(&points).unwrap();
+
+        assert_eq!(hull.vertices().len(), 8);
+        assert!(
+            corners
+                .iter()
+                .all(|corner| hull.vertices().contains(corner))
+        );
+        assert_eq!(hull.planes().len(), 6);
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            for normal in [axis, -axis] {
+                assert!(
+                    hull.planes().iter().any(|&(other, offset)| {
+                        other.abs_diff_eq(normal, 1e-6) && (offset - 1.0).abs() < 1e-6
+                    }),
+                    "no face facing {normal}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flat_points_have_no_hull() {
+        let square = [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 2.0),
+            Vec3::new(0.0, 1.0, 2.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ];
+        assert_eq!(ConvexHull::new(&square), None);
+
+        let line = [Vec3::ZERO, Vec3::X, Vec3::X * 2.0, Vec3::X * 3.0];
+        assert_eq!(ConvexHull::new(&line), None);
+
+        assert_eq!(ConvexHull::new(&[Vec3::ONE; 4]), None);
+        assert_eq!(ConvexHull::new(&[]), None);
+    }
+}
@@ -0,0 +1,1278 @@
+//! Contact generation between pairs of [`Collider`]s.
+
+use bevy::prelude::*;
+
+use super::{
+    collider::{Collider, Pose},
+    mesh_shape,
+};
+
+/// A single point where two colliders touch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactPoint {
+    /// World-space point halfway between the two surfaces.
+    pub point: Vec3,
+    /// World-space unit normal pointing from the first collider towards the second.
+    pub normal: Vec3,
+    /// How far the colliders overlap along `normal`.
+    pub depth: f32,
+}
+
+impl ContactPoint {
+    fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+/// Returns the points where `a` and `b` touch, with normals pointing from `a` to `b`.
+///
+/// The result is empty if they do not touch, or if neither shape has a volume to collide with
+/// (such as two planes).
+pub fn contacts(a: &Collider, pose_a: &Pose, b: &Collider, pose_b: &Pose) -> Vec<ContactPoint> {
+    use Collider::*;
+
+    match (a, b) {
+        (Sphere { radius: ra }, Sphere { radius: rb }) => {
+            sphere_sphere(pose_a.translation, *ra, pose_b.translation, *rb)
+                .into_iter()
+                .collect()
+        }
+        (Sphere { radius }, Plane { normal }) => {
+            sphere_plane(pose_a.translation, *radius, pose_b, *normal, false)
+                .into_iter()
+                .collect()
+        }
+        (Sphere { radius }, HalfSpace { normal }) => {
+            sphere_plane(pose_a.translation, *radius, pose_b, *normal, true)
+                .into_iter()
+                .collect()
+        }
+        (Sphere { radius }, Cuboid { half_extents }) => {
+            sphere_cuboid(pose_a.translation, *radius, pose_b, *half_extents)
+                .into_iter()
+                .collect()
+        }
+        (
+            Sphere { radius: rs },
+            Capsule {
+                radius: rc,
+                half_height,
+            },
+        ) => {
+            let (start, end) = capsule_segment(pose_b, *half_height);
+            let closest = closest_point_on_segment(pose_a.translation, start, end);
+            sphere_sphere(pose_a.translation, *rs, closest, *rc)
+                .into_iter()
+                .collect()
+        }
+        (
+            Capsule {
+                radius,
+                half_height,
+            },
+            Plane { normal },
+        ) => capsule_plane(pose_a, *radius, *half_height, pose_b, *normal, false),
+        (
+            Capsule {
+                radius,
+                half_height,
+            },
+            HalfSpace { normal },
+        ) => capsule_plane(pose_a, *radius, *half_height, pose_b, *normal, true),
+        (
+            Capsule {
+                radius,
+                half_height,
+            },
+            Cuboid { half_extents },
+        ) => capsule_cuboid(pose_a, *radius, *half_height, pose_b, *half_extents),
+        (
+            Capsule {
+                radius: ra,
+                half_height: ha,
+            },
+            Capsule {
+                radius: rb,
+                half_height: hb,
+            },
+        ) => {
+            let (a0, a1) = capsule_segment(pose_a, *ha);
+            let (b0, b1) = capsule_segment(pose_b, *hb);
+            let (pa, pb) = closest_points_on_segments(a0, a1, b0, b1);
+            sphere_sphere(pa, *ra, pb, *rb).into_iter().collect()
+        }
+        (Cuboid { half_extents }, Plane { normal }) => {
+            cuboid_plane(pose_a, *half_extents, pose_b, *normal, false)
+        }
+        (Cuboid { half_extents }, HalfSpace { normal }) => {
+            cuboid_plane(pose_a, *half_extents, pose_b, *normal, true)
+        }
+        (Cuboid { half_extents: ha }, Cuboid { half_extents: hb }) => {
+            cuboid_cuboid(pose_a, *ha, pose_b, *hb)
+        }
+        (Sphere { radius }, ConvexHull(hull)) => {
+            sphere_polytope(pose_a.translation, *radius, &Polytope::hull(hull, pose_b))
+                .into_iter()
+                .collect()
+        }
+        (Sphere { radius }, TriMesh(mesh)) => {
+            sphere_trimesh(pose_a.translation, *radius, mesh, pose_b)
+        }
+        (
+            Capsule {
+                radius,
+                half_height,
+            },
+            ConvexHull(hull),
+        ) => capsule_polytope(pose_a, *radius, *half_height, &Polytope::hull(hull, pose_b)),
+        (
+            Capsule {
+                radius,
+                half_height,
+            },
+            TriMesh(mesh),
+        ) => capsule_trimesh(pose_a, *radius, *half_height, mesh, pose_b),
+        (Cuboid { half_extents }, ConvexHull(hull)) => polytope_polytope(
+            &Polytope::cuboid(pose_a, *half_extents),
+            &Polytope::hull(hull, pose_b),
+        ),
+        (Cuboid { half_extents }, TriMesh(mesh)) => {
+            polytope_trimesh(&Polytope::cuboid(pose_a, *half_extents), mesh, pose_b)
+        }
+        (ConvexHull(hull), Plane { normal }) => {
+            vertices_plane(hull_vertices(hull, pose_a), pose_a, pose_b, *normal, false)
+        }
+        (ConvexHull(hull), HalfSpace { normal }) => {
+            vertices_plane(hull_vertices(hull, pose_a), pose_a, pose_b, *normal, true)
+        }
+        (ConvexHull(a), ConvexHull(b)) => {
+            polytope_polytope(&Polytope::hull(a, pose_a), &Polytope::hull(b, pose_b))
+        }
+        (ConvexHull(hull), TriMesh(mesh)) => {
+            polytope_trimesh(&Polytope::hull(hull, pose_a), mesh, pose_b)
+        }
+        (Plane { .. } | HalfSpace { .. }, Plane { .. } | HalfSpace { .. })
+        | (TriMesh(_), Plane { .. } | HalfSpace { .. } | TriMesh(_)) => Vec::new(),
+        // Every other combination is one of the above with the shapes swapped.
+        _ => contacts(b, pose_b, a, pose_a)
+            .into_iter()
+            .map(ContactPoint::flipped)
+            .collect(),
+    }
+}
+
+fn sphere_sphere(
+    center_a: Vec3,
+    radius_a: f32,
+    center_b: Vec3,
+    radius_b: f32,
+) -> Option<ContactPoint> {
+    let offset = center_b - center_a;
+    let distance = offset.length();
+    let depth = radius_a + radius_b - distance;
+    if depth < 0.0 {
+        return None;
+    }
+
+    // Concentric spheres have no preferred direction; push them apart vertically.
+    let normal = if distance > f32::EPSILON {
+        offset / distance
+    } else {
+        Vec3::Y
+    };
+    Some(ContactPoint {
+        point: center_a + normal * (radius_a - depth / 2.0),
+        normal,
+        depth,
+    })
+}
+
+/// Sphere `a` against a plane (`solid` false) or half-space (`solid` true) `b`.
+fn sphere_plane(
+    center: Vec3,
+    radius: f32,
+    pose: &Pose,
+    normal: Dir3,
+    solid: bool,
+) -> Option<ContactPoint> {
+    let (normal, distance) = plane_side(center, pose, normal, solid);
+    let depth = radius - distance;
+    if depth < 0.0 {
+        return None;
+    }
+
+    Some(ContactPoint {
+        point: center - normal * (radius + distance) / 2.0,
+        normal: -normal,
+        depth,
+    })
+}
+
+/// Returns the world-space normal of a plane facing `point`, and the distance of `point` in
+/// front of it.
+///
+/// A half-space always faces along its own normal, since everything behind it is solid. A
+/// two-sided plane faces whichever side `point` is on.
+fn plane_side(point: Vec3, pose: &Pose, normal: Dir3, solid: bool) -> (Vec3, f32) {
+    let normal = pose.rotation * normal.as_vec3();
+    let distance = (point - pose.translation).dot(normal);
+    if solid || distance >= 0.0 {
+        (normal, distance)
+    } else {
+        (-normal, -distance)
+    }
+}
+
+fn sphere_cuboid(
+    center: Vec3,
+    radius: f32,
+    pose: &Pose,
+    half_extents: Vec3,
+) -> Option<ContactPoint> {
+    let local = pose.inverse_transform_point(center);
+    let closest = local.clamp(-half_extents, half_extents);
+
+    let (local_normal, depth, local_surface) = if closest == local {
+        // The centre is inside the box: push it out through the nearest face.
+        let to_face = half_extents - local.abs();
+        let axis = if to_face.x <= to_face.y && to_face.x <= to_face.z {
+            Vec3::X
+        } else if to_face.y <= to_face.z {
+            Vec3::Y
+        } else {
+            Vec3::Z
+        };
+        let normal = axis * local.dot(axis).signum();
+        let distance_to_face = to_face.dot(axis);
+        let surface = local + normal * distance_to_face;
+        (normal, radius + distance_to_face, surface)
+    } else {
+        let offset = local - closest;
+        let distance = offset.length();
+        if distance > radius {
+            return None;
+        }
+        (offset / distance, radius - distance, closest)
+    };
+
+    // `local_normal` points from the box towards the sphere.
+    let normal = pose.rotation * local_normal;
+    let surface = pose.transform_point(local_surface);
+    Some(ContactPoint {
+        point: surface - normal * depth / 2.0,
+        normal: -normal,
+        depth,
+    })
+}
+
+/// The world-space end points of a capsule's inner segment.
+fn capsule_segment(pose: &Pose, half_height: f32) -> (Vec3, Vec3) {
+    let axis = pose.rotation * Vec3::Y * half_height;
+    (pose.translation - axis, pose.translation + axis)
+}
+
+fn capsule_plane(
+    pose: &Pose,
+    radius: f32,
+    half_height: f32,
+    plane: &Pose,
+    normal: Dir3,
+    solid: bool,
+) -> Vec<ContactPoint> {
+    // A two-sided plane is treated as a half-space facing the capsule's centre, so both ends
+    // are pushed out the same way.
+    let (facing, _) = plane_side(pose.translation, plane, normal, solid);
+    let facing = Dir3::new_unchecked(plane.rotation.inverse() * facing);
+    let (start, end) = capsule_segment(pose, half_height);
+    [start, end]
+        .into_iter()
+        .filter_map(|point| sphere_plane(point, radius, plane, facing, true))
+        .collect()
+}
+
+fn capsule_cuboid(
+    pose: &Pose,
+    radius: f32,
+    half_height: f32,
+    cuboid: &Pose,
+    half_extents: Vec3,
+) -> Vec<ContactPoint> {
+    let (start, end) = capsule_segment(pose, half_height);
+    capsule_convex(
+        start,
+        end,
+        |point| {
+            let local = cuboid.inverse_transform_point(point);
+            (local - local.clamp(-half_extents, half_extents)).length_squared()
+        },
+        |center| sphere_cuboid(center, radius, cuboid, half_extents),
+    )
+}
+
+/// Contacts between the capsule around `start`–`end` and a convex shape, given a measure of
+/// the distance from a point to the shape and the contact of a sphere against it.
+///
+/// The distance has to be convex along the segment, so that a ternary search finds the point
+/// of the segment closest to the shape.
+fn capsule_convex(
+    start: Vec3,
+    end: Vec3,
+    distance: impl Fn(Vec3) -> f32,
+    sphere: impl Fn(Vec3) -> Option<ContactPoint>,
+) -> Vec<ContactPoint> {
+    let distance_at = |t: f32| distance(start.lerp(end, t));
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    for _ in 0..24 {
+        let third = (high - low) / 3.0;
+        if distance_at(low + third) < distance_at(high - third) {
+            high -= third;
+        } else {
+            low += third;
+        }
+    }
+    let closest = start.lerp(end, (low + high) / 2.0);
+
+    // The end points keep a capsule lying flat on a box from rocking on a single contact.
+    let mut points = Vec::new();
+    for center in [closest, start, end] {
+        if let Some(contact) = sphere(center) {
+            push_unique(&mut points, contact);
+        }
+    }
+    points
+}
+
+/// Adds `contact` unless there already is one at the same point.
+fn push_unique(points: &mut Vec<ContactPoint>, contact: ContactPoint) {
+    if points
+        .iter()
+        .all(|other| other.point.distance_squared(contact.point) > 1e-6)
+    {
+        points.push(contact);
+    }
+}
+
+/// The eight corners of a box in world space.
+fn cuboid_vertices(pose: &Pose, half_extents: Vec3) -> [Vec3; 8] {
+    let mut vertices = [Vec3::ZERO; 8];
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        let sign = Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        *vertex = pose.transform_point(sign * half_extents);
+    }
+    vertices
+}
+
+fn cuboid_plane(
+    pose: &Pose,
+    half_extents: Vec3,
+    plane: &Pose,
+    normal: Dir3,
+    solid: bool,
+) -> Vec<ContactPoint> {
+    vertices_plane(
+        cuboid_vertices(pose, half_extents),
+        pose,
+        plane,
+        normal,
+        solid,
+    )
+}
+
+/// The corners of a polyhedron at `pose` against a plane or half-space.
+fn vertices_plane(
+    vertices: impl IntoIterator<Item = Vec3>,
+    pose: &Pose,
+    plane: &Pose,
+    normal: Dir3,
+    solid: bool,
+) -> Vec<ContactPoint> {
+    let (facing, _) = plane_side(pose.translation, plane, normal, solid);
+    vertices
+        .into_iter()
+        .filter_map(|vertex| {
+            let distance = (vertex - plane.translation).dot(facing);
+            (distance <= 0.0).then(|| ContactPoint {
+                point: vertex - facing * distance / 2.0,
+                normal: -facing,
+                depth: -distance,
+            })
+        })
+        .collect()
+}
+
+/// Separating axis test between two oriented boxes, with contact points from clipping the
+/// incident face against the reference face.
+fn cuboid_cuboid(pose_a: &Pose, half_a: Vec3, pose_b: &Pose, half_b: Vec3) -> Vec<ContactPoint> {
+    let axes_a = box_axes(pose_a);
+    let axes_b = box_axes(pose_b);
+    let offset = pose_b.translation - pose_a.translation;
+
+    let project = |axes: &[Vec3; 3], half: Vec3, axis: Vec3| {
+        axes[0].dot(axis).abs() * half.x
+            + axes[1].dot(axis).abs() * half.y
+            + axes[2].dot(axis).abs() * half.z
+    };
+    let overlap_on = |axis: Vec3| {
+        project(&axes_a, half_a, axis) + project(&axes_b, half_b, axis) - offset.dot(axis).abs()
+    };
+
+    #[derive(Clone, Copy)]
+    enum Feature {
+        FaceA(usize),
+        FaceB(usize),
+        Edges(usize, usize),
+    }
+
+    let mut best = (f32::MAX, Vec3::ZERO, Feature::FaceA(0));
+    for i in 0..3 {
+        for (axis, feature) in [
+            (axes_a[i], Feature::FaceA(i)),
+            (axes_b[i], Feature::FaceB(i)),
+        ] {
+            let overlap = overlap_on(axis);
+            if overlap < 0.0 {
+                return Vec::new();
+            }
+            if overlap < best.0 {
+                best = (overlap, axis, feature);
+            }
+        }
+    }
+    for (i, axis_a) in axes_a.iter().enumerate() {
+        for (j, axis_b) in axes_b.iter().enumerate() {
+            let axis = axis_a.cross(*axis_b);
+            let length = axis.length();
+            // Parallel edges are already covered by the face axes.
+            if length < 1e-5 {
+                continue;
+            }
+            let axis = axis / length;
+            let overlap = overlap_on(axis);
+            if overlap < 0.0 {
+                return Vec::new();
+            }
+            // Only prefer an edge contact if it is clearly better than the best face, since
+            // face contacts give much more stable manifolds.
+            if overlap < best.0 * 0.95 - 1e-3 {
+                best = (overlap, axis, Feature::Edges(i, j));
+            }
+        }
+    }
+
+    let (depth, mut normal, feature) = best;
+    if offset.dot(normal) < 0.0 {
+        normal = -normal;
+    }
+
+    match feature {
+        Feature::FaceA(i) => {
+            clip_faces(pose_a, &axes_a, half_a, i, pose_b, &axes_b, half_b, normal)
+        }
+        Feature::FaceB(i) => {
+            clip_faces(pose_b, &axes_b, half_b, i, pose_a, &axes_a, half_a, -normal)
+                .into_iter()
+                .map(ContactPoint::flipped)
+                .collect()
+        }
+        Feature::Edges(i, j) => {
+            // The edges of each box that reach furthest towards the other.
+            let support_edge =
+                |pose: &Pose, axes: &[Vec3; 3], half: Vec3, edge: usize, towards: Vec3| {
+                    let mut center = pose.translation;
+                    for k in 0..3 {
+                        if k != edge {
+                            center += axes[k] * half[k] * axes[k].dot(towards).signum();
+                        }
+                    }
+                    (
+                        center - axes[edge] * half[edge],
+                        center + axes[edge] * half[edge],
+                    )
+                };
+            let (a0, a1) = support_edge(pose_a, &axes_a, half_a, i, normal);
+            let (b0, b1) = support_edge(pose_b, &axes_b, half_b, j, -normal);
+            let (pa, pb) = closest_points_on_segments(a0, a1, b0, b1);
+            vec![ContactPoint {
+                point: (pa + pb) / 2.0,
+                normal,
+                depth,
+            }]
+        }
+    }
+}
+
+fn box_axes(pose: &Pose) -> [Vec3; 3] {
+    [
+        pose.rotation * Vec3::X,
+        pose.rotation * Vec3::Y,
+        pose.rotation * Vec3::Z,
+    ]
+}
+
+/// Clips the face of the incident box that faces the reference box against the reference face
+/// `face`, whose outward normal is `normal`, and keeps the points that are behind it.
+#[allow(clippy::too_many_arguments)]
+fn clip_faces(
+    reference: &Pose,
+    reference_axes: &[Vec3; 3],
+    reference_half: Vec3,
+    face: usize,
+    incident: &Pose,
+    incident_axes: &[Vec3; 3],
+    incident_half: Vec3,
+    normal: Vec3,
+) -> Vec<ContactPoint> {
+    // The incident face is the one most opposed to the reference normal.
+    let incident_face = (0..3)
+        .max_by(|&i, &j| {
+            incident_axes[i]
+                .dot(normal)
+                .abs()
+                .total_cmp(&incident_axes[j].dot(normal).abs())
+        })
+        .unwrap_or(0);
+    let facing = -incident_axes[incident_face] * incident_axes[incident_face].dot(normal).signum();
+    let center = incident.translation + facing * incident_half[incident_face];
+    let (u, v) = ((incident_face + 1) % 3, (incident_face + 2) % 3);
+    let (edge_u, edge_v) = (
+        incident_axes[u] * incident_half[u],
+        incident_axes[v] * incident_half[v],
+    );
+    let mut polygon = vec![
+        center + edge_u + edge_v,
+        center - edge_u + edge_v,
+        center - edge_u - edge_v,
+        center + edge_u - edge_v,
+    ];
+
+    // Clip against the four side planes of the reference face.
+    for side in [(face + 1) % 3, (face + 2) % 3] {
+        let axis = reference_axes[side];
+        let offset = reference.translation.dot(axis);
+        polygon = clip_polygon(&polygon, axis, offset + reference_half[side]);
+        polygon = clip_polygon(&polygon, -axis, -offset + reference_half[side]);
+    }
+
+    let face_center = reference.translation + normal * reference_half[face];
+    polygon
+        .into_iter()
+        .filter_map(|point| {
+            let separation = (point - face_center).dot(normal);
+            (separation <= 0.0).then(|| ContactPoint {
+                point: point - normal * separation / 2.0,
+                normal,
+                depth: -separation,
+            })
+        })
+        .collect()
+}
+
+/// Sutherland–Hodgman clipping of a convex polygon to the half-space `dot(normal, p) <= offset`.
+fn clip_polygon(polygon: &[Vec3], normal: Vec3, offset: f32) -> Vec<Vec3> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, &start) in polygon.iter().enumerate() {
+        let end = polygon[(i + 1) % polygon.len()];
+        let start_distance = start.dot(normal) - offset;
+        let end_distance = end.dot(normal) - offset;
+        if start_distance <= 0.0 {
+            clipped.push(start);
+        }
+        if (start_distance <= 0.0) != (end_distance <= 0.0) {
+            let t = start_distance / (start_distance - end_distance);
+            clipped.push(start.lerp(end, t));
+        }
+    }
+    clipped
+}
+
+/// A convex polyhedron in world space.
+struct Polytope {
+    vertices: Vec<Vec3>,
+    /// Outward unit normals of the faces, with each face's distance from the origin along it.
+    planes: Vec<(Vec3, f32)>,
+}
+
+impl Polytope {
+    fn hull(hull: &mesh_shape::ConvexHull, pose: &Pose) -> Self {
+        Self {
+            vertices: hull_vertices(hull, pose).collect(),
+            planes: hull
+                .planes()
+                .iter()
+                .map(|&(normal, offset)| {
+                    let normal = pose.rotation * normal;
+                    (normal, offset + normal.dot(pose.translation))
+                })
+                .collect(),
+        }
+    }
+
+    fn cuboid(pose: &Pose, half_extents: Vec3) -> Self {
+        Self {
+            vertices: cuboid_vertices(pose, half_extents).to_vec(),
+            planes: box_axes(pose)
+                .into_iter()
+                .zip(half_extents.to_array())
+                .flat_map(|(axis, half)| {
+                    let offset = axis.dot(pose.translation);
+                    [(axis, offset + half), (-axis, half - offset)]
+                })
+                .collect(),
+        }
+    }
+
+    /// The normal of the face `point` is furthest in front of, and how far in front of it it
+    /// is. The distance is negative inside the polytope.
+    fn deepest_face(&self, point: Vec3) -> (Vec3, f32) {
+        self.planes
+            .iter()
+            .map(|&(normal, offset)| (normal, normal.dot(point) - offset))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((Vec3::Y, f32::INFINITY))
+    }
+}
+
+/// The corners of a convex hull in world space.
+fn hull_vertices<'a>(
+    hull: &'a mesh_shape::ConvexHull,
+    pose: &'a Pose,
+) -> impl Iterator<Item = Vec3> + 'a {
+    hull.vertices()
+        .iter()
+        .map(|&vertex| pose.transform_point(vertex))
+}
+
+/// Sphere against a convex polytope, measuring the distance to the face the centre is furthest
+/// in front of.
+///
+/// That is exact against faces, and errs towards touching near edges and corners.
+fn sphere_polytope(center: Vec3, radius: f32, polytope: &Polytope) -> Option<ContactPoint> {
+    let (normal, distance) = polytope.deepest_face(center);
+    let depth = radius - distance;
+    if depth < 0.0 {
+        return None;
+    }
+
+    Some(ContactPoint {
+        point: center - normal * (radius + distance) / 2.0,
+        normal: -normal,
+        depth,
+    })
+}
+
+fn capsule_polytope(
+    pose: &Pose,
+    radius: f32,
+    half_height: f32,
+    polytope: &Polytope,
+) -> Vec<ContactPoint> {
+    let (start, end) = capsule_segment(pose, half_height);
+    capsule_convex(
+        start,
+        end,
+        |point| polytope.deepest_face(point).1,
+        |center| sphere_polytope(center, radius, polytope),
+    )
+}
+
+/// Separating axis test between two convex polytopes over their face normals, with contacts
+/// at the corners of each that are inside the other.
+///
+/// Edge-against-edge axes are left out, so crossed edges are only caught once a corner gets
+/// through.
+fn polytope_polytope(a: &Polytope, b: &Polytope) -> Vec<ContactPoint> {
+    // The face of `reference` the vertices of `other` are furthest in front of, and how far the
+    // deepest of them is in front of it.
+    let least_overlap = |reference: &Polytope, other: &Polytope| {
+        reference
+            .planes
+            .iter()
+            .map(|&(normal, offset)| {
+                let deepest = other
+                    .vertices
+                    .iter()
+                    .map(|vertex| normal.dot(*vertex) - offset)
+                    .fold(f32::INFINITY, f32::min);
+                (normal, deepest)
+            })
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+    };
+    let (Some((normal_a, separation_a)), Some((normal_b, separation_b))) =
+        (least_overlap(a, b), least_overlap(b, a))
+    else {
+        return Vec::new();
+    };
+    if separation_a > 0.0 || separation_b > 0.0 {
+        return Vec::new();
+    }
+    let (normal, depth) = if separation_a >= separation_b {
+        (normal_a, -separation_a)
+    } else {
+        (-normal_b, -separation_b)
+    };
+
+    let mut points = Vec::new();
+    for (vertices, inside, direction) in [(&b.vertices, a, 1.0), (&a.vertices, b, -1.0)] {
+        for &vertex in vertices {
+            let (_, distance) = inside.deepest_face(vertex);
+            if distance <= 0.0 {
+                let vertex_depth = (-distance).min(depth);
+                push_unique(
+                    &mut points,
+                    ContactPoint {
+                        point: vertex + normal * direction * vertex_depth / 2.0,
+                        normal,
+                        depth: vertex_depth,
+                    },
+                );
+            }
+        }
+    }
+
+    if points.is_empty() {
+        // The polytopes overlap on every face normal, but no corner is inside the other: the
+        // corner of `b` reaching furthest into `a` is the best guess.
+        let deepest = b
+            .vertices
+            .iter()
+            .copied()
+            .min_by(|x, y| x.dot(normal).total_cmp(&y.dot(normal)));
+        points.extend(deepest.map(|vertex| ContactPoint {
+            point: vertex + normal * depth / 2.0,
+            normal,
+            depth,
+        }));
+    }
+    points
+}
+
+/// The world-space triangles of `mesh` whose bounds overlap `min`–`max`.
+fn nearby_triangles<'a>(
+    mesh: &'a mesh_shape::TriMesh,
+    pose: &'a Pose,
+    min: Vec3,
+    max: Vec3,
+) -> impl Iterator<Item = [Vec3; 3]> + 'a {
+    mesh.triangles()
+        .map(|triangle| triangle.map(|corner| pose.transform_point(corner)))
+        .filter(move |triangle| {
+            let lower = triangle[0].min(triangle[1]).min(triangle[2]);
+            let upper = triangle[0].max(triangle[1]).max(triangle[2]);
+            lower.cmple(max).all() && upper.cmpge(min).all()
+        })
+}
+
+/// The unit normal of the side a triangle is wound counter-clockwise from, if it has an area.
+fn triangle_normal([a, b, c]: &[Vec3; 3]) -> Option<Vec3> {
+    (*b - *a).cross(*c - *a).try_normalize()
+}
+
+/// Sphere against the front of a single triangle.
+fn sphere_triangle(center: Vec3, radius: f32, triangle: &[Vec3; 3]) -> Option<ContactPoint> {
+    let normal = triangle_normal(triangle)?;
+    // Triangles are one-sided: a centre behind one has gone through it.
+    if (center - triangle[0]).dot(normal) < 0.0 {
+        return None;
+    }
+    let closest = closest_point_on_triangle(center, triangle);
+    let offset = center - closest;
+    let distance = offset.length();
+    let depth = radius - distance;
+    if depth < 0.0 {
+        return None;
+    }
+
+    // `outward` points from the triangle towards the sphere.
+    let outward = if distance > f32::EPSILON {
+        offset / distance
+    } else {
+        normal
+    };
+    Some(ContactPoint {
+        point: closest - outward * depth / 2.0,
+        normal: -outward,
+        depth,
+    })
+}
+
+fn sphere_trimesh(
+    center: Vec3,
+    radius: f32,
+    mesh: &mesh_shape::TriMesh,
+    pose: &Pose,
+) -> Vec<ContactPoint> {
+    let mut points = Vec::new();
+    for triangle in nearby_triangles(mesh, pose, center - radius, center + radius) {
+        if let Some(contact) = sphere_triangle(center, radius, &triangle) {
+            push_unique(&mut points, contact);
+        }
+    }
+    points
+}
+
+fn capsule_trimesh(
+    pose: &Pose,
+    radius: f32,
+    half_height: f32,
+    mesh: &mesh_shape::TriMesh,
+    mesh_pose: &Pose,
+) -> Vec<ContactPoint> {
+    let (start, end) = capsule_segment(pose, half_height);
+    let (min, max) = (start.min(end) - radius, start.max(end) + radius);
+
+    let mut points = Vec::new();
+    for triangle in nearby_triangles(mesh, mesh_pose, min, max) {
+        let contacts = capsule_convex(
+            start,
+            end,
+            |point| point.distance_squared(closest_point_on_triangle(point, &triangle)),
+            |center| sphere_triangle(center, radius, &triangle),
+        );
+        for contact in contacts {
+            push_unique(&mut points, contact);
+        }
+    }
+    points
+}
+
+/// A convex polytope against a triangle mesh: corners of the polytope just behind triangles,
+/// and corners of triangles inside the polytope.
+fn polytope_trimesh(
+    polytope: &Polytope,
+    mesh: &mesh_shape::TriMesh,
+    pose: &Pose,
+) -> Vec<ContactPoint> {
+    let Some((min, max)) = polytope
+        .vertices
+        .iter()
+        .map(|&vertex| (vertex, vertex))
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+    else {
+        return Vec::new();
+    };
+    // Corners further behind a triangle than this have gone all the way through it.
+    let max_depth = (max - min).length() / 2.0;
+
+    let mut points = Vec::new();
+    for triangle in nearby_triangles(mesh, pose, min, max) {
+        let Some(normal) = triangle_normal(&triangle) else {
+            continue;
+        };
+
+        for &vertex in &polytope.vertices {
+            let distance = (vertex - triangle[0]).dot(normal);
+            let projected = vertex - normal * distance;
+            if distance <= 0.0
+                && distance > -max_depth
+                && projected.distance_squared(closest_point_on_triangle(projected, &triangle))
+                    < 1e-8
+            {
+                push_unique(
+                    &mut points,
+                    ContactPoint {
+                        point: vertex - normal * distance / 2.0,
+                        normal: -normal,
+                        depth: -distance,
+                    },
+                );
+            }
+        }
+
+        for &corner in &triangle {
+            let (_, distance) = polytope.deepest_face(corner);
+            if distance <= 0.0 {
+                push_unique(
+                    &mut points,
+                    ContactPoint {
+                        point: corner + normal * distance / 2.0,
+                        normal: -normal,
+                        depth: -distance,
+                    },
+                );
+            }
+        }
+    }
+    points
+}
+
+/// The point on the triangle `a`–`b`–`c` closest to `point`.
+///
+/// From Ericson, *Real-Time Collision Detection*, section 5.1.5.
+pub fn closest_point_on_triangle(point: Vec3, [a, b, c]: &[Vec3; 3]) -> Vec3 {
+    let (a, b, c) = (*a, *b, *c);
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = point - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = (va + vb + vc).recip();
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+/// The point on the segment from `start` to `end` closest to `point`.
+pub fn closest_point_on_segment(point: Vec3, start: Vec3, end: Vec3) -> Vec3 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return start;
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    start + segment * t
+}
+
+/// The closest pair of points between the segments `a0`–`a1` and `b0`–`b1`.
+///
+/// From Ericson, *Real-Time Collision Detection*, section 5.1.9.
+pub fn closest_points_on_segments(a0: Vec3, a1: Vec3, b0: Vec3, b1: Vec3) -> (Vec3, Vec3) {
+    let d1 = a1 - a0;
+    let d2 = b1 - b0;
+    let r = a0 - b0;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= f32::EPSILON && e <= f32::EPSILON {
+        return (a0, b0);
+    }
+    let (s, t) = if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denominator = a * e - b * b;
+            let mut s = if denominator > f32::EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    (a0 + d1 * s, b0 + d2 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+
+    #[test]
+    fn sphere_resting_on_box_touches_its_top_face() {
+        let half_extents = Vec3::new(1.0, 0.5, 1.0);
+        let cuboid = Collider::cuboid(half_extents);
+        let tilt = Quat::from_rotation_z(0.3);
+        let box_pose = Pose::new(Vec3::new(2.0, 1.0, -1.0), tilt);
+        let top = tilt * Vec3::Y;
+        let radius = 0.5;
+        let sphere = Collider::sphere(radius);
+        let sphere_pose = Pose::new(
+            box_pose.translation + top * (half_extents.y + radius - 0.02) + tilt * Vec3::X * 0.3,
+            Quat::IDENTITY,
+        );
+
+        let [contact] = contacts(&sphere, &sphere_pose, &cuboid, &box_pose)[..] else {
+            panic!("expected one contact");
+        };
+        assert!(contact.normal.abs_diff_eq(-top, 1e-5));
+        assert!((contact.depth - 0.02).abs() < 1e-5);
+        // Halfway between the face and the lowest point of the sphere, so within half the depth
+        // of the face and over it.
+        let local = box_pose.inverse_transform_point(contact.point);
+        assert!(
+            (local.y - (half_extents.y - contact.depth / 2.0)).abs() < 1e-5,
+            "contact at {local} in the box's space"
+        );
+        assert!(local.xz().abs().cmple(half_extents.xz()).all());
+
+        let [swapped] = contacts(&cuboid, &box_pose, &sphere, &sphere_pose)[..] else {
+            panic!("expected one contact");
+        };
+        assert!(swapped.point.abs_diff_eq(contact.point, 1e-6));
+        assert!(swapped.normal.abs_diff_eq(top, 1e-5));
+    }
+
+    /// Checks that every contact has `normal` and `depth`, and returns their points.
+    fn points_with(contacts: &[ContactPoint], normal: Vec3, depth: f32) -> Vec<Vec3> {
+        for contact in contacts {
+            assert!(
+                contact.normal.abs_diff_eq(normal, 1e-5),
+                "normal {}, expected {normal}",
+                contact.normal
+            );
+            assert!(
+                (contact.depth - depth).abs() < 1e-5,
+                "depth {}, expected {depth}",
+                contact.depth
+            );
+        }
+        contacts.iter().map(|contact| contact.point).collect()
+    }
+
+    /// Checks that `b`-`a` gives the same contacts as `a`-`b`, with the normals flipped.
+    fn assert_symmetric(a: &Collider, pose_a: &Pose, b: &Collider, pose_b: &Pose) {
+        let forwards = contacts(a, pose_a, b, pose_b);
+        let backwards = contacts(b, pose_b, a, pose_a);
+        assert_eq!(forwards.len(), backwards.len());
+        for contact in forwards {
+            assert!(
+                backwards
+                    .iter()
+                    .any(|other| other.point.abs_diff_eq(contact.point, 1e-5)
+                        && other.normal.abs_diff_eq(-contact.normal, 1e-5)
+                        && (other.depth - contact.depth).abs() < 1e-5),
+                "{contact:?} has no flipped counterpart in {backwards:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sphere_touches_a_plane_from_either_side() {
+        let plane = Collider::Plane { normal: Dir3::Y };
+        let plane_pose = Pose::new(Vec3::new(0.0, 1.0, 0.0), Quat::IDENTITY);
+        let sphere = Collider::sphere(0.5);
+
+        for side in [1.0, -1.0] {
+            let sphere_pose = Pose::new(Vec3::new(2.0, 1.0 + side * 0.4, 0.0), Quat::IDENTITY);
+            let found = contacts(&sphere, &sphere_pose, &plane, &plane_pose);
+            let [point] = points_with(&found, Vec3::NEG_Y * side, 0.1)[..] else {
+                panic!("expected one contact, got {found:?}");
+            };
+            assert!(point.abs_diff_eq(Vec3::new(2.0, 1.0 - side * 0.05, 0.0), 1e-5));
+            assert_symmetric(&sphere, &sphere_pose, &plane, &plane_pose);
+        }
+
+        let clear = Pose::new(Vec3::new(2.0, 1.6, 0.0), Quat::IDENTITY);
+        assert!(contacts(&sphere, &clear, &plane, &plane_pose).is_empty());
+    }
+
+    #[test]
+    fn sphere_inside_a_half_space_is_pushed_out_along_its_normal() {
+        let tilt = Quat::from_rotation_x(0.4);
+        let up = tilt * Vec3::Y;
+        let half_space = Collider::half_space(Dir3::Y);
+        let half_space_pose = Pose::new(Vec3::new(1.0, -1.0, 0.0), tilt);
+        let sphere = Collider::sphere(0.5);
+        // Below the surface, where a two-sided plane would push it further down.
+        let sphere_pose = Pose::new(half_space_pose.translation - up, Quat::IDENTITY);
+
+        let found = contacts(&sphere, &sphere_pose, &half_space, &half_space_pose);
+        assert_eq!(points_with(&found, -up, 1.5).len(), 1);
+        assert_symmetric(&sphere, &sphere_pose, &half_space, &half_space_pose);
+
+        let plane = Collider::Plane { normal: Dir3::Y };
+        assert!(contacts(&sphere, &sphere_pose, &plane, &half_space_pose).is_empty());
+    }
+
+    #[test]
+    fn box_on_a_half_space_touches_at_its_four_lower_corners() {
+        let half_extents = Vec3::new(0.5, 0.25, 1.0);
+        let cuboid = Collider::cuboid(half_extents);
+        let box_pose = Pose::new(Vec3::new(3.0, 0.23, -1.0), Quat::from_rotation_y(0.7));
+        let ground = Collider::half_space(Dir3::Y);
+        let ground_pose = Pose::new(Vec3::ZERO, Quat::IDENTITY);
+
+        let found = contacts(&cuboid, &box_pose, &ground, &ground_pose);
+        let points = points_with(&found, Vec3::NEG_Y, 0.02);
+        assert_eq!(points.len(), 4);
+        for point in points {
+            let local = box_pose.inverse_transform_point(point);
+            assert!((local.y + half_extents.y - 0.01).abs() < 1e-5, "{local}");
+            assert!(local.xz().abs().abs_diff_eq(half_extents.xz(), 1e-5));
+        }
+        assert_symmetric(&cuboid, &box_pose, &ground, &ground_pose);
+    }
+
+    #[test]
+    fn capsule_lying_on_the_ground_touches_at_both_ends() {
+        let capsule = Collider::capsule(0.3, 1.0);
+        // Lying along X.
+        let capsule_pose = Pose::new(Vec3::new(0.0, 0.28, 2.0), Quat::from_rotation_z(FRAC_PI_2));
+        let ground_pose = Pose::new(Vec3::ZERO, Quat::IDENTITY);
+
+        for ground in [
+            Collider::half_space(Dir3::Y),
+            Collider::Plane { normal: Dir3::Y },
+        ] {
+            let found = contacts(&capsule, &capsule_pose, &ground, &ground_pose);
+            let mut xs: Vec<f32> = points_with(&found, Vec3::NEG_Y, 0.02)
+                .iter()
+                .map(|point| point.x)
+                .collect();
+            xs.sort_by(f32::total_cmp);
+            assert!(
+                xs.len() == 2 && (xs[0] + 1.0).abs() < 1e-5 && (xs[1] - 1.0).abs() < 1e-5,
+                "contacts at x = {xs:?}"
+            );
+            assert_symmetric(&capsule, &capsule_pose, &ground, &ground_pose);
+        }
+
+        // Standing up, only the lower end touches.
+        let standing = Pose::new(Vec3::new(0.0, 1.28, 2.0), Quat::IDENTITY);
+        let ground = Collider::half_space(Dir3::Y);
+        let found = contacts(&capsule, &standing, &ground, &ground_pose);
+        assert_eq!(points_with(&found, Vec3::NEG_Y, 0.02).len(), 1);
+    }
+
+    #[test]
+    fn crossed_capsules_touch_where_their_axes_pass() {
+        let capsule = Collider::capsule(0.25, 1.0);
+        let lower = Pose::new(Vec3::ZERO, Quat::from_rotation_z(FRAC_PI_2));
+        let upper = Pose::new(Vec3::new(0.3, 0.48, -0.2), Quat::from_rotation_x(FRAC_PI_2));
+
+        let found = contacts(&capsule, &lower, &capsule, &upper);
+        let [point] = points_with(&found, Vec3::Y, 0.02)[..] else {
+            panic!("expected one contact, got {found:?}");
+        };
+        assert!(
+            point.abs_diff_eq(Vec3::new(0.3, 0.24, 0.0), 1e-5),
+            "{point}"
+        );
+        assert_symmetric(&capsule, &lower, &capsule, &upper);
+    }
+
+    #[test]
+    fn capsule_lying_on_a_box_stays_on_its_top_face() {
+        let half_extents = Vec3::new(2.0, 0.5, 2.0);
+        let cuboid = Collider::cuboid(half_extents);
+        let box_pose = Pose::new(Vec3::new(0.0, 0.5, 0.0), Quat::IDENTITY);
+        let capsule = Collider::capsule(0.3, 1.0);
+        let capsule_pose = Pose::new(
+            Vec3::new(0.5, 1.28, 0.0),
+            Quat::from_rotation_y(0.6) * Quat::from_rotation_z(FRAC_PI_2),
+        );
+
+        let found = contacts(&capsule, &capsule_pose, &cuboid, &box_pose);
+        let points = points_with(&found, Vec3::NEG_Y, 0.02);
+        assert!(
+            points.len() >= 2,
+            "a lying capsule needs two contacts to rest"
+        );
+        for point in points {
+            assert!((point.y - 0.99).abs() < 1e-5, "{point}");
+        }
+        assert_symmetric(&capsule, &capsule_pose, &cuboid, &box_pose);
+    }
+
+    #[test]
+    fn stacked_boxes_clip_to_the_overlap_of_their_faces() {
+        let cuboid = Collider::cuboid(Vec3::splat(0.5));
+        // The whole stack is tilted so no axis lines up with the world.
+        let tilt = Quat::from_euler(EulerRot::XYZ, 0.3, 0.2, -0.4);
+        let up = tilt * Vec3::Y;
+        let lower = Pose::new(Vec3::new(1.0, 2.0, 3.0), tilt);
+        let upper = Pose::new(
+            lower.translation + up * 0.98,
+            tilt * Quat::from_rotation_y(0.4),
+        );
+
+        let found = contacts(&cuboid, &lower, &cuboid, &upper);
+        let points = points_with(&found, up, 0.02);
+        // A square turned on top of another overlaps it in an octagon.
+        assert_eq!(points.len(), 8);
+        for point in points {
+            let in_lower = lower.inverse_transform_point(point);
+            let in_upper = upper.inverse_transform_point(point);
+            assert!((in_lower.y - 0.49).abs() < 1e-5, "{in_lower}");
+            assert!(in_lower.xz().abs().cmple(Vec2::splat(0.5 + 1e-5)).all());
+            assert!(in_upper.xz().abs().cmple(Vec2::splat(0.5 + 1e-5)).all());
+        }
+        assert_symmetric(&cuboid, &lower, &cuboid, &upper);
+
+        let apart = Pose::new(lower.translation + up * 1.01, upper.rotation);
+        assert!(contacts(&cuboid, &lower, &cuboid, &apart).is_empty());
+    }
+
+    #[test]
+    fn small_box_on_a_large_one_touches_at_its_corners() {
+        let small = Collider::cuboid(Vec3::splat(0.25));
+        let large = Collider::cuboid(Vec3::new(2.0, 0.5, 2.0));
+        let large_pose = Pose::new(Vec3::ZERO, Quat::IDENTITY);
+        let small_pose = Pose::new(Vec3::new(0.5, 0.74, -0.3), Quat::from_rotation_y(0.9));
+
+        for (a, pose_a, b, pose_b, normal) in [
+            (&small, &small_pose, &large, &large_pose, Vec3::NEG_Y),
+            (&large, &large_pose, &small, &small_pose, Vec3::Y),
+        ] {
+            let found = contacts(a, pose_a, b, pose_b);
+            let points = points_with(&found, normal, 0.01);
+            assert_eq!(points.len(), 4, "{found:?}");
+            for point in points {
+                let local = small_pose.inverse_transform_point(point);
+                assert!(local.xz().abs().abs_diff_eq(Vec2::splat(0.25), 1e-5));
+                assert!((point.y - 0.495).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn boxes_crossed_edge_to_edge_touch_at_one_point() {
+        let cuboid = Collider::cuboid(Vec3::splat(0.5));
+        // Each box stands on an edge, the lower one along Z and the upper one along X.
+        let lower = Pose::new(Vec3::ZERO, Quat::from_rotation_z(FRAC_PI_4));
+        let upper = Pose::new(
+            Vec3::new(0.0, SQRT_2 - 0.02, 0.0),
+            Quat::from_rotation_x(FRAC_PI_4),
+        );
+
+        let found = contacts(&cuboid, &lower, &cuboid, &upper);
+        let [point] = points_with(&found, Vec3::Y, 0.02)[..] else {
+            panic!("expected one contact, got {found:?}");
+        };
+        assert!(
+            point.abs_diff_eq(Vec3::new(0.0, SQRT_2 / 2.0 - 0.01, 0.0), 1e-5),
+            "{point}"
+        );
+        assert_symmetric(&cuboid, &lower, &cuboid, &upper);
+    }
+}
@@ -0,0 +1,169 @@
+//! Ray intersection with [`Collider`]s.
+
+use bevy::prelude::*;
+
+use super::collider::{Collider, Pose};
+
+impl Collider {
+    /// The distance along `ray` at which it first enters this collider at `pose`, and the
+    /// world-space surface normal there, if that is within `max_distance`.
+    ///
+    /// Rays that start inside a solid shape do not hit it. Planes and triangle meshes are hit
+    /// from either side.
+    pub fn cast_ray(&self, pose: &Pose, ray: Ray3d, max_distance: f32) -> Option<(f32, Vec3)> {
+        let origin = pose.inverse_transform_point(ray.origin);
+        let direction = pose.rotation.inverse() * ray.direction.as_vec3();
+
+        let (distance, normal) = match self {
+            Collider::Sphere { radius } => ray_sphere(origin, direction, Vec3::ZERO, *radius),
+            Collider::Plane { normal } => ray_plane(origin, direction, normal.as_vec3(), false),
+            Collider::HalfSpace { normal } => ray_plane(origin, direction, normal.as_vec3(), true),
+            Collider::Cuboid { half_extents } => ray_planes(
+                origin,
+                direction,
+                [Vec3::X, Vec3::Y, Vec3::Z]
+                    .into_iter()
+                    .zip(half_extents.to_array())
+                    .flat_map(|(axis, half)| [(axis, half), (-axis, half)]),
+            ),
+            Collider::Capsule {
+                radius,
+                half_height,
+            } => ray_capsule(origin, direction, *radius, *half_height),
+            Collider::ConvexHull(hull) => {
+                ray_planes(origin, direction, hull.planes().iter().copied())
+            }
+            Collider::TriMesh(mesh) => mesh
+                .triangles()
+                .filter_map(|triangle| ray_triangle(origin, direction, triangle))
+                .min_by(|a, b| a.0.total_cmp(&b.0)),
+        }?;
+
+        (distance <= max_distance).then(|| (distance, pose.rotation * normal))
+    }
+}
+
+fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<(f32, Vec3)> {
+    let offset = origin - center;
+    let b = offset.dot(direction);
+    let c = offset.length_squared() - radius * radius;
+    // Starting inside, or outside and pointing away.
+    if c <= 0.0 || b > 0.0 {
+        return None;
+    }
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    Some((distance, (offset + direction * distance) / radius))
+}
+
+/// A plane through the origin, hit from the front only if `solid`.
+fn ray_plane(origin: Vec3, direction: Vec3, normal: Vec3, solid: bool) -> Option<(f32, Vec3)> {
+    let height = origin.dot(normal);
+    let speed = direction.dot(normal);
+    if speed == 0.0 || (solid && height <= 0.0) {
+        return None;
+    }
+    let distance = -height / speed;
+    (distance >= 0.0).then(|| (distance, normal * height.signum()))
+}
+
+/// The intersection of half-spaces `dot(normal, p) <= offset`, clipping the ray against each in
+/// turn.
+fn ray_planes(
+    origin: Vec3,
+    direction: Vec3,
+    planes: impl IntoIterator<Item = (Vec3, f32)>,
+) -> Option<(f32, Vec3)> {
+    let (mut enter, mut exit) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut entry_normal = None;
+    for (normal, offset) in planes {
+        let height = normal.dot(origin) - offset;
+        let speed = normal.dot(direction);
+        if speed == 0.0 {
+            // Parallel to the plane, and entirely in front of it.
+            if height > 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let distance = -height / speed;
+        if speed < 0.0 {
+            if distance > enter {
+                enter = distance;
+                entry_normal = Some(normal);
+            }
+        } else {
+            exit = exit.min(distance);
+        }
+    }
+
+    // Entering behind the origin means the ray started inside, or the shape is behind it.
+    if enter > exit || enter < 0.0 {
+        return None;
+    }
+    entry_normal.map(|normal| (enter, normal))
+}
+
+/// A capsule along the Y axis.
+fn ray_capsule(
+    origin: Vec3,
+    direction: Vec3,
+    radius: f32,
+    half_height: f32,
+) -> Option<(f32, Vec3)> {
+    // The cylinder between the two hemispheres.
+    let (flat_origin, flat_direction) = (origin.xz(), direction.xz());
+    let a = flat_direction.length_squared();
+    if a > f32::EPSILON {
+        let b = flat_origin.dot(flat_direction);
+        let c = flat_origin.length_squared() - radius * radius;
+        let discriminant = b * b - a * c;
+        if c > 0.0 && b < 0.0 && discriminant >= 0.0 {
+            let distance = (-b - discriminant.sqrt()) / a;
+            let point = origin + direction * distance;
+            if point.y.abs() <= half_height {
+                return Some((distance, Vec3::new(point.x, 0.0, point.z) / radius));
+            }
+        }
+    }
+
+    [Vec3::NEG_Y, Vec3::Y]
+        .into_iter()
+        .filter_map(|end| ray_sphere(origin, direction, end * half_height, radius))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// Möller–Trumbore ray-triangle intersection, hitting both sides.
+fn ray_triangle(origin: Vec3, direction: Vec3, [a, b, c]: [Vec3; 3]) -> Option<(f32, Vec3)> {
+    let edge_ab = b - a;
+    let edge_ac = c - a;
+    let p = direction.cross(edge_ac);
+    let determinant = edge_ab.dot(p);
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+    let inverse = determinant.recip();
+
+    let offset = origin - a;
+    let u = offset.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(edge_ab);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge_ac.dot(q) * inverse;
+    if distance < 0.0 {
+        return None;
+    }
+
+    // Face the normal back along the ray, whichever side was hit.
+    let normal = edge_ab.cross(edge_ac).normalize();
+    Some((distance, normal * -normal.dot(direction).signum()))
+}
@@ -0,0 +1,212 @@
+//! Colliders built from the meshes of glTF scenes, chosen by node name.
+
+use bevy::{
+    prelude::*,
+    render::mesh::{PrimitiveTopology, VertexAttributeValues},
+    scene::SceneInstanceReady,
+    transform::helper::TransformHelper,
+};
+
+use super::{Collider, RigidBody};
+
+/// The collider a glTF node asks for with the suffix of its name.
+///
+/// Blender's `.001`-style numbering of duplicates is ignored, so `Wall-col.003` is a
+/// [`MeshColliderKind::TriMesh`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshColliderKind {
+    /// `-col`: the triangles of the mesh, for static level geometry.
+    TriMesh,
+    /// `-convex`: the convex hull of the mesh's vertices.
+    ConvexHull,
+    /// `-box`: the bounding box of the mesh.
+    Cuboid,
+    /// `-sphere`: the smallest sphere around the mesh's bounding box centre.
+    Sphere,
+    /// `-capsule`: an upright capsule fitted to the bounding box.
+    Capsule,
+}
+
+impl MeshColliderKind {
+    const SUFFIXES: [(&str, MeshColliderKind); 5] = [
+        ("-col", MeshColliderKind::TriMesh),
+        ("-convex", MeshColliderKind::ConvexHull),
+        ("-box", MeshColliderKind::Cuboid),
+        ("-sphere", MeshColliderKind::Sphere),
+        ("-capsule", MeshColliderKind::Capsule),
+    ];
+
+    /// The kind of collider `name` asks for, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = match name.rsplit_once('.') {
+            Some((base, number))
+                if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                base
+            }
+            _ => name,
+        };
+        Self::SUFFIXES
+            .into_iter()
+            .find(|(suffix, _)| name.ends_with(suffix))
+            .map(|(_, kind)| kind)
+    }
+
+    /// Builds a collider from the vertices of `mesh`, scaled by `scale`.
+    ///
+    /// Colliders are not scaled along with their entity, so the scale is baked in. Primitives
+    /// are centred on their entity, so they are returned with the offset to the centre of the
+    /// mesh, in the mesh's unscaled local space.
+    pub fn collider(self, mesh: &Mesh, scale: Vec3) -> Option<(Vec3, Collider)> {
+        let positions: Vec<Vec3> = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+            VertexAttributeValues::Float32x3(positions) => positions
+                .iter()
+                .map(|&position| Vec3::from(position) * scale)
+                .collect(),
+            _ => return None,
+        };
+        let (min, max) = positions
+            .iter()
+            .map(|&position| (position, position))
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))?;
+        let center = (min + max) / 2.0;
+        let half_extents = (max - min) / 2.0;
+        let offset = center / scale;
+
+        match self {
+            MeshColliderKind::TriMesh => {
+                if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+                    return None;
+                }
+                let indices: Vec<u32> = match mesh.indices() {
+                    Some(indices) => indices.iter().map(|index| index as u32).collect(),
+                    None => (0..positions.len() as u32).collect(),
+                };
+                let triangles = indices
+                    .chunks_exact(3)
+                    .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                    .collect();
+                Some((Vec3::ZERO, Collider::trimesh(positions, triangles)))
+            }
+            MeshColliderKind::ConvexHull => {
+                Collider::convex_hull(&positions).map(|collider| (Vec3::ZERO, collider))
+            }
+            MeshColliderKind::Cuboid => Some((offset, Collider::cuboid(half_extents))),
+            MeshColliderKind::Sphere => {
+                let radius = positions
+                    .iter()
+                    .map(|position| position.distance(center))
+                    .fold(0.0, f32::max);
+                Some((offset, Collider::sphere(radius)))
+            }
+            MeshColliderKind::Capsule => {
+                let radius = half_extents.x.max(half_extents.z);
+                let half_height = (half_extents.y - radius).max(0.0);
+                Some((offset, Collider::capsule(radius, half_height)))
+            }
+        }
+    }
+}
+
+/// Gives the meshes of a freshly spawned scene the colliders their glTF node names ask for.
+///
+/// The colliders have no [`RigidBody`], so they are static: this is meant for levels, not for
+/// scenes that move. Scenes on or under a body that is not [`RigidBody::Static`] are skipped,
+/// since the physics only moves colliders on the body entity itself; such a body needs its
+/// collider added by hand.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn colliders_from_scene(
+    trigger: Trigger<SceneInstanceReady>,
+    mut commands: Commands,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    bodies: Query<&RigidBody>,
+    mesh_entities: Query<(&Mesh3d, Option<&Name>, Option<&ChildOf>)>,
+    names: Query<&Name>,
+    meshes: Res<Assets<Mesh>>,
+    transform_helper: TransformHelper,
+) {
+    let root = trigger.target();
+    let moves = std::iter::once(root)
+        .chain(parents.iter_ancestors(root))
+        .any(|entity| {
+            bodies
+                .get(entity)
+                .is_ok_and(|body| *body != RigidBody::Static)
+        });
+    if moves {
+        return;
+    }
+
+    for entity in children.iter_descendants(root) {
+        let Ok((mesh, name, child_of)) = mesh_entities.get(entity) else {
+            continue;
+        };
+        // glTF primitives are spawned as children of their node, and Blender names the node.
+        let kind = name
+            .and_then(|name| MeshColliderKind::from_name(name))
+            .or_else(|| {
+                child_of
+                    .and_then(|child_of| names.get(child_of.parent()).ok())
+                    .and_then(|name| MeshColliderKind::from_name(name))
+            });
+        let (Some(kind), Some(mesh)) = (kind, meshes.get(&mesh.0)) else {
+            continue;
+        };
+        let Ok(global_transform) = transform_helper.compute_global_transform(entity) else {
+            continue;
+        };
+        let Some((offset, collider)) = kind.collider(mesh, global_transform.scale()) else {
+            warn!("Could not build a {kind:?} collider for {entity}");
+            continue;
+        };
+
+        if offset == Vec3::ZERO {
+            commands.entity(entity).insert(collider);
+        } else {
+            commands
+                .entity(entity)
+                .with_child((Transform::from_translation(offset), collider));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_suffixes_choose_the_collider() {
+        for (name, kind) in [
+            ("Wall-col", MeshColliderKind::TriMesh),
+            ("Rock-convex", MeshColliderKind::ConvexHull),
+            ("Crate-box", MeshColliderKind::Cuboid),
+            ("Ball-sphere", MeshColliderKind::Sphere),
+            ("Pillar-capsule", MeshColliderKind::Capsule),
+        ] {
+            assert_eq!(MeshColliderKind::from_name(name), Some(kind), "{name}");
+            let numbered = format!("{name}.003");
+            assert_eq!(
+                MeshColliderKind::from_name(&numbered),
+                Some(kind),
+                "{numbered}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_names_have_no_collider() {
+        for name in [
+            "Icosphere.001",
+            "Wall",
+            "col",
+            "Wall-col.",
+            "Wall-col.0a1",
+            "Wall-colour",
+            "Wall-box-lid",
+            "",
+        ] {
+            assert_eq!(MeshColliderKind::from_name(name), None, "{name}");
+        }
+    }
+}
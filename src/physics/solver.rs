@@ -0,0 +1,417 @@
+//! Sequential-impulse constraint solver.
+//!
+//! Every contact point becomes a constraint that keeps the bodies from moving into each other
+//! along the contact normal, plus a friction constraint in the contact plane. Every [`Joint`]
+//! adds a constraint for each degree of freedom it removes. The constraints are solved one at a
+//! time, over and over, each iteration correcting what the previous one disturbed.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::{
+    Contacts, Mass, RigidBody, Velocity,
+    joint::{Joint, joint_rows},
+};
+
+/// Contact solver [`Resource`].
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SolverSettings {
+    /// How many times every contact and joint is solved per step. More iterations make stacks
+    /// and chains of joints stiffer.
+    pub velocity_iterations: usize,
+    /// The fraction of the penetration depth, or of a joint's drift, corrected per step.
+    ///
+    /// This is Baumgarte stabilisation: position errors are corrected by adding a little
+    /// velocity, rather than moving bodies directly.
+    pub baumgarte: f32,
+    /// Overlap in metres that is left alone, so resting contacts stay touching instead of
+    /// jittering in and out of contact.
+    pub penetration_slop: f32,
+    /// Closing speed in metres per second below which contacts do not bounce, so resting
+    /// bodies come to rest.
+    pub restitution_threshold: f32,
+    /// Reuse the previous step's impulses as a starting guess, so stacks settle in fewer
+    /// iterations.
+    pub warm_starting: bool,
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        Self {
+            velocity_iterations: 10,
+            baumgarte: 0.2,
+            penetration_slop: 0.005,
+            restitution_threshold: 1.0,
+            warm_starting: true,
+        }
+    }
+}
+
+/// Surface properties of a collider. Colliders without one use the default.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsMaterial {
+    /// Coulomb friction coefficient: the ratio of the largest friction force to the normal
+    /// force.
+    pub friction: f32,
+    /// How much of the closing speed is kept as separating speed: 0 is no bounce, 1 is a
+    /// perfectly elastic bounce.
+    pub restitution: f32,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            friction: 0.5,
+            restitution: 0.0,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    /// The friction and restitution of two materials in contact.
+    fn combine(self, other: Self) -> Self {
+        Self {
+            friction: (self.friction * other.friction).sqrt(),
+            restitution: self.restitution.max(other.restitution),
+        }
+    }
+}
+
+/// How close a contact point has to be to one from the previous step to inherit its impulses.
+const WARM_START_DISTANCE: f32 = 0.05;
+
+/// The accumulated impulses of a contact point, kept for warm starting the next step.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct CachedImpulse {
+    point: Vec3,
+    normal: f32,
+    friction: Vec3,
+}
+
+/// A body as the solver sees it.
+pub(super) struct SolverBody {
+    pub(super) entity: Option<Entity>,
+    pub(super) center: Vec3,
+    pub(super) rotation: Quat,
+    pub(super) inverse_mass: f32,
+    pub(super) inverse_inertia: Mat3,
+    pub(super) velocity: Velocity,
+}
+
+impl SolverBody {
+    fn apply_impulse(&mut self, impulse: Vec3, offset: Vec3) {
+        self.velocity.linear += impulse * self.inverse_mass;
+        self.velocity.angular += self.inverse_inertia * offset.cross(impulse);
+    }
+}
+
+struct ContactConstraint {
+    a: usize,
+    b: usize,
+    point: Vec3,
+    offset_a: Vec3,
+    offset_b: Vec3,
+    normal: Vec3,
+    tangents: [Vec3; 2],
+    normal_mass: f32,
+    tangent_mass: [f32; 2],
+    friction: f32,
+    /// Separating speed the normal constraint aims for.
+    target_speed: f32,
+    normal_impulse: f32,
+    friction_impulse: Vec3,
+}
+
+impl ContactConstraint {
+    fn relative_velocity(&self, bodies: &[SolverBody]) -> Vec3 {
+        bodies[self.b].velocity.at_point(self.offset_b)
+            - bodies[self.a].velocity.at_point(self.offset_a)
+    }
+
+    fn apply(&self, bodies: &mut [SolverBody], impulse: Vec3) {
+        bodies[self.a].apply_impulse(-impulse, self.offset_a);
+        bodies[self.b].apply_impulse(impulse, self.offset_b);
+    }
+}
+
+/// `1 / k` where `k` is how much the relative speed along `direction` changes per unit impulse.
+fn effective_mass(
+    a: &SolverBody,
+    offset_a: Vec3,
+    b: &SolverBody,
+    offset_b: Vec3,
+    direction: Vec3,
+) -> f32 {
+    let angular = |body: &SolverBody, offset: Vec3| {
+        (body.inverse_inertia * offset.cross(direction))
+            .cross(offset)
+            .dot(direction)
+    };
+    let k = a.inverse_mass + b.inverse_mass + angular(a, offset_a) + angular(b, offset_b);
+    if k > 0.0 { k.recip() } else { 0.0 }
+}
+
+/// Resolves the contacts found this step and every [`Joint`] by changing the velocities of
+/// dynamic bodies.
+pub(super) fn solve_constraints(
+    time: Res<Time>,
+    settings: Res<SolverSettings>,
+    contacts: Res<Contacts>,
+    mut joints: Query<&mut Joint>,
+    mut bodies: Query<(&RigidBody, &Transform, &mut Velocity, &Mass)>,
+    materials: Query<&PhysicsMaterial>,
+    mut cache: Local<HashMap<(Entity, Entity), Vec<CachedImpulse>>>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    // Colliders without a body are static, and all of them can share one immovable body. It
+    // sits at the origin so joints to it are anchored in world space.
+    let mut solver_bodies = vec![SolverBody {
+        entity: None,
+        center: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        inverse_mass: 0.0,
+        inverse_inertia: Mat3::ZERO,
+        velocity: Velocity::default(),
+    }];
+    let mut indices = HashMap::new();
+    let mut body_index = |entity: Entity, solver_bodies: &mut Vec<SolverBody>| {
+        *indices.entry(entity).or_insert_with(|| {
+            let Ok((body, transform, velocity, mass)) = bodies.get(entity) else {
+                return 0;
+            };
+            let (inverse_mass, inverse_inertia) = if body.is_dynamic() {
+                (
+                    mass.inverse_mass(),
+                    mass.inverse_inertia(transform.rotation),
+                )
+            } else {
+                (0.0, Mat3::ZERO)
+            };
+            solver_bodies.push(SolverBody {
+                entity: Some(entity),
+                center: transform.translation,
+                rotation: transform.rotation,
+                inverse_mass,
+                inverse_inertia,
+                velocity: *velocity,
+            });
+            solver_bodies.len() - 1
+        })
+    };
+
+    let mut constraints = Vec::new();
+    let mut manifold_ranges = Vec::with_capacity(contacts.manifolds.len());
+    for manifold in &contacts.manifolds {
+        // The broad phase does not always report a pair in the same order, so put it in a
+        // fixed one for warm starting to find it again next step.
+        let (entity_a, entity_b, sign) = if manifold.a < manifold.b {
+            (manifold.a, manifold.b, 1.0)
+        } else {
+            (manifold.b, manifold.a, -1.0)
+        };
+        let start = constraints.len();
+        let a = body_index(entity_a, &mut solver_bodies);
+        let b = body_index(entity_b, &mut solver_bodies);
+        let material = materials
+            .get(manifold.a)
+            .copied()
+            .unwrap_or_default()
+            .combine(materials.get(manifold.b).copied().unwrap_or_default());
+
+        for contact in &manifold.points {
+            let (body_a, body_b) = (&solver_bodies[a], &solver_bodies[b]);
+            let offset_a = contact.point - body_a.center;
+            let offset_b = contact.point - body_b.center;
+            let normal = contact.normal * sign;
+            let (tangent, bitangent) = normal.any_orthonormal_pair();
+
+            let mut constraint = ContactConstraint {
+                a,
+                b,
+                point: contact.point,
+                offset_a,
+                offset_b,
+                normal,
+                tangents: [tangent, bitangent],
+                normal_mass: effective_mass(body_a, offset_a, body_b, offset_b, normal),
+                tangent_mass: [
+                    effective_mass(body_a, offset_a, body_b, offset_b, tangent),
+                    effective_mass(body_a, offset_a, body_b, offset_b, bitangent),
+                ],
+                friction: material.friction,
+                target_speed: 0.0,
+                normal_impulse: 0.0,
+                friction_impulse: Vec3::ZERO,
+            };
+
+            // Bounce off with the closing speed from before the solver touched anything, or
+            // push the overlap out, whichever is faster.
+            let closing_speed = -constraint.relative_velocity(&solver_bodies).dot(normal);
+            let bounce = if closing_speed > settings.restitution_threshold {
+                material.restitution * closing_speed
+            } else {
+                0.0
+            };
+            let correction =
+                settings.baumgarte / dt * (contact.depth - settings.penetration_slop).max(0.0);
+            constraint.target_speed = bounce.max(correction);
+
+            constraints.push(constraint);
+        }
+        manifold_ranges.push((entity_a, entity_b, start..constraints.len()));
+    }
+
+    let rate = settings.baumgarte / dt;
+    let mut rows = Vec::new();
+    for mut joint in &mut joints {
+        let a = body_index(joint.body_a, &mut solver_bodies);
+        let b = body_index(joint.body_b, &mut solver_bodies);
+        rows.extend(joint_rows(&mut joint, (a, b), &solver_bodies, rate, dt));
+    }
+
+    if settings.warm_starting {
+        for (a, b, range) in &manifold_ranges {
+            let Some(cached) = cache.get_mut(&(*a, *b)) else {
+                continue;
+            };
+            for constraint in &mut constraints[range.clone()] {
+                let Some(index) = cached
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, previous)| {
+                        previous.point.distance(constraint.point) < WARM_START_DISTANCE
+                    })
+                    .min_by(|(_, x), (_, y)| {
+                        x.point
+                            .distance_squared(constraint.point)
+                            .total_cmp(&y.point.distance_squared(constraint.point))
+                    })
+                    .map(|(index, _)| index)
+                else {
+                    continue;
+                };
+                // Each impulse is handed on once: when a manifold gains points, giving several
+                // of them the same impulse would push the bodies apart harder every step.
+                let previous = cached.swap_remove(index);
+                constraint.normal_impulse = previous.normal;
+                // The contact plane may have tilted a little since; drop whatever now points
+                // out of it.
+                constraint.friction_impulse = previous.friction
+                    - constraint.normal * previous.friction.dot(constraint.normal);
+                let impulse =
+                    constraint.normal * constraint.normal_impulse + constraint.friction_impulse;
+                constraint.apply(&mut solver_bodies, impulse);
+            }
+        }
+    }
+
+    for _ in 0..settings.velocity_iterations {
+        for row in &mut rows {
+            row.solve(&mut solver_bodies);
+        }
+        for constraint in &mut constraints {
+            // Friction first, limited by the normal impulse of the previous iteration, so the
+            // non-penetration constraint gets the last word.
+            let max_friction = constraint.friction * constraint.normal_impulse;
+            let relative_velocity = constraint.relative_velocity(&solver_bodies);
+            let mut friction_impulse = constraint.friction_impulse;
+            for (tangent, mass) in constraint.tangents.iter().zip(constraint.tangent_mass) {
+                friction_impulse -= *tangent * relative_velocity.dot(*tangent) * mass;
+            }
+            // Coulomb's law: the friction impulse stays inside a cone around the normal.
+            friction_impulse = friction_impulse.clamp_length_max(max_friction);
+            let delta = friction_impulse - constraint.friction_impulse;
+            constraint.friction_impulse = friction_impulse;
+            constraint.apply(&mut solver_bodies, delta);
+
+            let normal_speed = constraint
+                .relative_velocity(&solver_bodies)
+                .dot(constraint.normal);
+            // Accumulate and clamp the total rather than each increment, so a later iteration
+            // can take back what an earlier one overdid without ever pulling bodies together.
+            let normal_impulse = (constraint.normal_impulse
+                + (constraint.target_speed - normal_speed) * constraint.normal_mass)
+                .max(0.0);
+            let delta = normal_impulse - constraint.normal_impulse;
+            constraint.normal_impulse = normal_impulse;
+            constraint.apply(&mut solver_bodies, constraint.normal * delta);
+        }
+    }
+
+    cache.clear();
+    for (a, b, range) in manifold_ranges {
+        cache.insert(
+            (a, b),
+            constraints[range]
+                .iter()
+                .map(|constraint| CachedImpulse {
+                    point: constraint.point,
+                    normal: constraint.normal_impulse,
+                    friction: constraint.friction_impulse,
+                })
+                .collect(),
+        );
+    }
+
+    for solver_body in &solver_bodies {
+        let Some(entity) = solver_body.entity else {
+            continue;
+        };
+        let Ok((body, _, mut velocity, _)) = bodies.get_mut(entity) else {
+            continue;
+        };
+        if body.is_dynamic() {
+            velocity.set_if_neq(solver_body.velocity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Collider, test_harness::PhysicsTestApp};
+
+    #[test]
+    fn box_tower_stays_standing() {
+        let mut app = PhysicsTestApp::new();
+        app.spawn((
+            RigidBody::Static,
+            Collider::half_space(Dir3::Y),
+            Transform::default(),
+        ));
+        let half_extents = Vec3::splat(0.5);
+        let boxes: Vec<(Entity, Vec3)> = (0..6)
+            .map(|level| {
+                // Small gaps, so the boxes drop into place rather than starting in contact.
+                let start = Vec3::new(1.0, 0.51 + level as f32 * 1.01, -2.0);
+                let entity = app.spawn((
+                    RigidBody::Dynamic,
+                    Collider::cuboid(half_extents),
+                    Mass::cuboid(1.0, half_extents),
+                    Transform::from_translation(start),
+                ));
+                (entity, start)
+            })
+            .collect();
+
+        let ticks = (10.0 / app.timestep()).round() as u32;
+        app.step(ticks);
+
+        for (level, &(entity, start)) in boxes.iter().enumerate() {
+            let transform = app.transform(entity);
+            let resting = Vec3::new(start.x, 0.5 + level as f32, start.z);
+            assert!(
+                transform.translation.abs_diff_eq(resting, 0.05),
+                "box {level} at {}, expected about {resting}",
+                transform.translation
+            );
+            assert!(transform.rotation.angle_between(Quat::IDENTITY) < 0.02);
+            let velocity = app.velocity(entity);
+            assert!(velocity.linear.length() < 0.05 && velocity.angular.length() < 0.05);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use super::{Mass, Pose, RigidBody, Velocity};
+
+/// Pulls a point on a [`RigidBody::Dynamic`] body towards a target with a damped spring.
+///
+/// The spring is specified by how it behaves rather than by its stiffness, so it feels the
+/// same whatever the mass of the body.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    /// Attachment point in the body's local space. Like [`Collider`](super::Collider)s, it
+    /// ignores the body's scale.
+    pub anchor: Vec3,
+    /// World-space point the anchor is pulled towards.
+    pub target: Vec3,
+    /// How many times per second the body would oscillate without damping.
+    pub frequency: f32,
+    /// 1 stops the body at the target without overshooting; less lets it bounce.
+    pub damping_ratio: f32,
+}
+
+impl Spring {
+    pub fn new(anchor: Vec3, target: Vec3) -> Self {
+        Self {
+            anchor,
+            target,
+            frequency: 3.0,
+            damping_ratio: 0.7,
+        }
+    }
+
+    /// The world-space position of the anchor on a body at `transform`.
+    pub fn anchor_point(&self, transform: &Transform) -> Vec3 {
+        Pose::from(transform).transform_point(self.anchor)
+    }
+}
+
+/// Accelerates bodies with a [`Spring`] towards its target.
+pub(super) fn apply_springs(
+    time: Res<Time>,
+    mut bodies: Query<(&RigidBody, &Transform, &mut Velocity, &Mass, &Spring)>,
+) {
+    let dt = time.delta_secs();
+
+    for (body, transform, mut velocity, mass, spring) in &mut bodies {
+        if !body.is_dynamic() {
+            continue;
+        }
+
+        let anchor = spring.anchor_point(transform);
+        let offset = anchor - transform.translation;
+        let stretch = spring.target - anchor;
+        let angular_frequency = std::f32::consts::TAU * spring.frequency;
+        let acceleration = stretch * angular_frequency * angular_frequency
+            - velocity.at_point(offset) * 2.0 * spring.damping_ratio * angular_frequency;
+
+        // Pulling off-centre also turns the body, the way a hand would.
+        let impulse = acceleration * mass.mass * dt;
+        velocity.linear += acceleration * dt;
+        velocity.angular += mass.inverse_inertia(transform.rotation) * offset.cross(impulse);
+    }
+}
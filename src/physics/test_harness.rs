@@ -0,0 +1,63 @@
+//! A headless [`App`] for testing the physics, stepped one [`FixedUpdate`] tick at a time.
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use super::{PhysicsPlugin, Velocity};
+
+/// An app running only the [`PhysicsPlugin`].
+///
+/// Every frame takes exactly one fixed timestep, so [`step`](PhysicsTestApp::step) runs the
+/// physics a known number of times. Without the
+/// [`SimulationPlugin`](crate::simulation::SimulationPlugin), nothing interpolates the
+/// [`Transform`]s, so they are where the physics left them.
+pub struct PhysicsTestApp {
+    app: App,
+}
+
+impl PhysicsTestApp {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PhysicsPlugin));
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+        Self { app }
+    }
+
+    /// The length of one physics step, in seconds.
+    pub fn timestep(&self) -> f32 {
+        self.world()
+            .resource::<Time<Fixed>>()
+            .timestep()
+            .as_secs_f32()
+    }
+
+    /// Runs the physics `ticks` times.
+    pub fn step(&mut self, ticks: u32) {
+        let fixed_time = |app: &App| app.world().resource::<Time<Fixed>>().elapsed();
+        let timestep = self.world().resource::<Time<Fixed>>().timestep();
+        let end = fixed_time(&self.app) + timestep * ticks;
+        while fixed_time(&self.app) < end {
+            self.app.update();
+        }
+    }
+
+    pub fn spawn(&mut self, bundle: impl Bundle) -> Entity {
+        self.world_mut().spawn(bundle).id()
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn transform(&self, entity: Entity) -> Transform {
+        *self.world().get::<Transform>(entity).unwrap()
+    }
+
+    pub fn velocity(&self, entity: Entity) -> Velocity {
+        *self.world().get::<Velocity>(entity).unwrap()
+    }
+}
@@ -0,0 +1,341 @@
+use bevy::{
+    ecs::system::SystemParam,
+    picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings},
+    prelude::*,
+};
+use std::f32::consts::TAU;
+
+use crate::{
+    cursor_grab::CursorGrab,
+    input_map::{Action, ActionInput},
+    orbit_camera::is_hovered,
+    physics::{Collider, Pose},
+};
+
+/// Picks the mesh or collider under the cursor, sending hover and click events.
+pub struct ScenePickingPlugin;
+
+impl Plugin for ScenePickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hover>()
+            .add_event::<HoverStarted>()
+            .add_event::<HoverEnded>()
+            .add_event::<Clicked>()
+            .add_systems(Update, pick_under_cursor);
+    }
+}
+
+/// How far from the camera the cursor picks entities.
+const MAX_PICK_DISTANCE: f32 = 1000.0;
+
+/// Rays around the edge of a sphere cast, see [`SceneRaycast::cast_sphere_filtered`].
+pub const SPHERE_CAST_RAYS: usize = 8;
+
+/// Where a ray hit an entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub point: Vec3,
+    /// World-space unit normal of the surface that was hit.
+    pub normal: Vec3,
+    /// Distance along the ray.
+    pub distance: f32,
+}
+
+/// Casts rays against every mesh and every [`Collider`].
+#[derive(SystemParam)]
+pub struct SceneRaycast<'w, 's> {
+    meshes: MeshRayCast<'w, 's>,
+    colliders: Query<'w, 's, (Entity, &'static Collider, &'static GlobalTransform)>,
+}
+
+impl SceneRaycast<'_, '_> {
+    /// The closest mesh or collider hit by `ray` within `max_distance`.
+    pub fn cast_ray(&mut self, ray: Ray3d, max_distance: f32) -> Option<RayHit> {
+        self.cast_ray_filtered(ray, max_distance, |_| true)
+    }
+
+    /// The closest mesh or collider hit by `ray` within `max_distance`, ignoring entities for
+    /// which `filter` returns `false`.
+    pub fn cast_ray_filtered(
+        &mut self,
+        ray: Ray3d,
+        max_distance: f32,
+        filter: impl Fn(Entity) -> bool,
+    ) -> Option<RayHit> {
+        let settings = MeshRayCastSettings::default()
+            .with_filter(&filter)
+            .never_early_exit();
+        let mesh_hit = self
+            .meshes
+            .cast_ray(ray, &settings)
+            .first()
+            .filter(|(_, hit)| hit.distance <= max_distance)
+            .map(|(entity, hit)| RayHit {
+                entity: *entity,
+                point: hit.point,
+                normal: hit.normal,
+                distance: hit.distance,
+            });
+
+        let collider_hit = self
+            .colliders
+            .iter()
+            .filter(|(entity, ..)| filter(*entity))
+            .filter_map(|(entity, collider, global_transform)| {
+                let (distance, normal) =
+                    collider.cast_ray(&Pose::from(global_transform), ray, max_distance)?;
+                Some(RayHit {
+                    entity,
+                    point: ray.get_point(distance),
+                    normal,
+                    distance,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        [mesh_hit, collider_hit]
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// The closest mesh or collider hit by a sphere of `radius` swept along `ray` within
+    /// `max_distance`, ignoring entities for which `filter` returns `false`.
+    ///
+    /// The sphere is approximated by a ray through its centre and [`SPHERE_CAST_RAYS`] rays
+    /// around its silhouette, so geometry thin enough to fit between them can be missed. The
+    /// distance is how far the sphere travels before touching something.
+    pub fn cast_sphere_filtered(
+        &mut self,
+        ray: Ray3d,
+        radius: f32,
+        max_distance: f32,
+        filter: impl Fn(Entity) -> bool,
+    ) -> Option<RayHit> {
+        let (a, b) = ray.direction.as_vec3().any_orthonormal_pair();
+        let offsets = std::iter::once(Vec3::ZERO).chain((0..SPHERE_CAST_RAYS).map(|i| {
+            let angle = TAU * i as f32 / SPHERE_CAST_RAYS as f32;
+            (a * angle.cos() + b * angle.sin()) * radius
+        }));
+        offsets
+            .filter_map(|offset| {
+                let edge_ray = Ray3d::new(ray.origin + offset, ray.direction);
+                self.cast_ray_filtered(edge_ray, max_distance, &filter)
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+}
+
+/// What is under the cursor [`Resource`].
+#[derive(Resource, Debug, Default)]
+pub struct Hover {
+    hovered: Option<(Entity, RayHit)>,
+}
+
+impl Hover {
+    /// The closest entity under the cursor, if any.
+    pub fn hit(&self) -> Option<RayHit> {
+        self.hovered.map(|(_, hit)| hit)
+    }
+
+    /// The camera the cursor is picking through.
+    pub fn camera(&self) -> Option<Entity> {
+        self.hovered.map(|(camera, _)| camera)
+    }
+}
+
+/// Sent when the cursor moves onto an entity through `camera`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct HoverStarted {
+    pub camera: Entity,
+    pub hit: RayHit,
+}
+
+/// Sent when the cursor moves off an entity.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct HoverEnded(pub Entity);
+
+/// Sent when [`Action::Select`] is pressed over an entity.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Clicked {
+    pub camera: Entity,
+    pub hit: RayHit,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pick_under_cursor(
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    window: Single<&Window>,
+    cursor_grab: Res<CursorGrab>,
+    actions: ActionInput,
+    mut raycast: SceneRaycast,
+    mut hover: ResMut<Hover>,
+    mut started: EventWriter<HoverStarted>,
+    mut ended: EventWriter<HoverEnded>,
+    mut clicked: EventWriter<Clicked>,
+) {
+    // A grabbed cursor is hidden, so it is not pointing at anything.
+    let cursor_position = window
+        .cursor_position()
+        .filter(|_| !cursor_grab.is_grabbed());
+    let hovered = cursor_position.and_then(|cursor_position| {
+        let (entity, camera, global_transform) = cameras
+            .iter()
+            .find(|(_, camera, _)| camera.is_active && is_hovered(camera, cursor_position))?;
+        let ray = camera
+            .viewport_to_world(global_transform, cursor_position)
+            .ok()?;
+        raycast
+            .cast_ray(ray, MAX_PICK_DISTANCE)
+            .map(|hit| (entity, hit))
+    });
+
+    let previous = hover.hovered.map(|(_, hit)| hit.entity);
+    let current = hovered.map(|(_, hit)| hit.entity);
+    if previous != current {
+        if let Some(entity) = previous {
+            ended.write(HoverEnded(entity));
+        }
+        if let Some((camera, hit)) = hovered {
+            started.write(HoverStarted { camera, hit });
+        }
+    }
+    hover.hovered = hovered;
+
+    if let Some((camera, hit)) = hovered.filter(|_| actions.just_pressed(Action::Select)) {
+        clicked.write(Clicked { camera, hit });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestApp;
+    use bevy::{
+        ecs::{event::EventCursor, system::RunSystemOnce},
+        render::mesh::MeshAabb,
+    };
+
+    /// Where an entity spawned at `translation` is, including for the frame it is spawned in.
+    fn placed_at(translation: Vec3) -> (Transform, GlobalTransform) {
+        let transform = Transform::from_translation(translation);
+        (transform, GlobalTransform::from(transform))
+    }
+
+    /// Spawns a unit cube mesh at `translation`.
+    fn spawn_cube(app: &mut TestApp, translation: Vec3) -> Entity {
+        let mesh = Mesh::from(Cuboid::default());
+        // Without a renderer, nothing works out the bounds and visibility the ray cast checks.
+        let aabb = mesh.compute_aabb().unwrap();
+        let mut view_visibility = ViewVisibility::default();
+        view_visibility.set();
+        let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(mesh);
+        app.world_mut()
+            .spawn((
+                Mesh3d(mesh),
+                aabb,
+                placed_at(translation),
+                InheritedVisibility::VISIBLE,
+                view_visibility,
+            ))
+            .id()
+    }
+
+    #[test]
+    fn hovering_and_clicking_pick_the_nearest_entity() {
+        let mut app = TestApp::looking_at_origin();
+        // All on the line from the camera to the origin, under the cursor.
+        let far = spawn_cube(&mut app, Vec3::ZERO);
+        let middle = app
+            .world_mut()
+            .spawn((Collider::sphere(0.5), placed_at(Vec3::new(0.0, 3.0, 3.0))))
+            .id();
+        let near = spawn_cube(&mut app, Vec3::new(0.0, 5.0, 5.4));
+
+        let mut started_cursor = EventCursor::<HoverStarted>::default();
+        let mut ended_cursor = EventCursor::<HoverEnded>::default();
+        let mut clicked_cursor = EventCursor::<Clicked>::default();
+        let mut update = |app: &mut TestApp| {
+            app.update();
+            let world = app.world();
+            let started: Vec<Entity> = started_cursor
+                .read(world.resource::<Events<HoverStarted>>())
+                .map(|event| event.hit.entity)
+                .collect();
+            let ended: Vec<Entity> = ended_cursor
+                .read(world.resource::<Events<HoverEnded>>())
+                .map(|event| event.0)
+                .collect();
+            let clicked: Vec<Entity> = clicked_cursor
+                .read(world.resource::<Events<Clicked>>())
+                .map(|event| event.hit.entity)
+                .collect();
+            (started, ended, clicked)
+        };
+
+        assert_eq!(update(&mut app), (vec![near], vec![], vec![]));
+        let hit = app.world().resource::<Hover>().hit().unwrap();
+        assert!(hit.point.abs_diff_eq(Vec3::new(0.0, 5.5, 5.5), 1e-4));
+        assert_eq!(hit.normal, Vec3::Y);
+        assert_eq!(update(&mut app), (vec![], vec![], vec![]));
+
+        app.world_mut().despawn(near);
+        assert_eq!(update(&mut app), (vec![middle], vec![near], vec![]));
+        app.world_mut().despawn(middle);
+        assert_eq!(update(&mut app), (vec![far], vec![middle], vec![]));
+
+        app.press_mouse(MouseButton::Left);
+        let (_, _, clicked) = update(&mut app);
+        assert_eq!(clicked, [far]);
+        assert_eq!(app.world().resource::<Hover>().camera(), Some(app.camera()));
+    }
+
+    #[test]
+    fn sphere_cast_hits_beside_the_ray_and_skips_filtered_entities() {
+        let mut app = TestApp::looking_at_origin();
+        let world = app.world_mut();
+        // Too far to the side for the ray to hit, but not for a sphere swept along it.
+        let beside = world
+            .spawn((Collider::sphere(0.5), Transform::from_xyz(0.7, 0.0, 0.0)))
+            .id();
+        let behind = world
+            .spawn((
+                Collider::cuboid(Vec3::splat(0.5)),
+                Transform::from_xyz(0.0, 0.0, 5.0),
+            ))
+            .id();
+        app.update();
+
+        let ray = Ray3d::new(Vec3::new(0.0, 0.0, -10.0), Dir3::Z);
+        let cast = move |radius: f32, skip: Option<Entity>| {
+            move |mut raycast: SceneRaycast| {
+                raycast.cast_sphere_filtered(ray, radius, MAX_PICK_DISTANCE, |entity| {
+                    Some(entity) != skip
+                })
+            }
+        };
+        let world = app.world_mut();
+
+        let thin = world.run_system_once(cast(0.0, None)).unwrap().unwrap();
+        assert_eq!(thin.entity, behind);
+        assert!((thin.distance - 14.5).abs() < 1e-4);
+
+        let wide = world.run_system_once(cast(0.5, None)).unwrap().unwrap();
+        assert_eq!(wide.entity, beside);
+        assert!(wide.distance < 10.0);
+
+        let filtered = world
+            .run_system_once(cast(0.5, Some(beside)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(filtered.entity, behind);
+        assert!((filtered.distance - 14.5).abs() < 1e-4);
+
+        let unfiltered = world
+            .run_system_once(cast(0.5, Some(behind)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(unfiltered.entity, beside);
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+use crate::orbit_camera::{CameraSettings, is_hovered};
+
+/// Switches [`CameraSettings`] cameras between perspective and orthographic projection while
+/// keeping the focus point framed the same way.
+pub struct ProjectionSwitchPlugin;
+
+impl Plugin for ProjectionSwitchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SetProjection>()
+            .add_systems(Update, (toggle_projection, set_projection).chain());
+    }
+}
+
+/// The kinds of [`Projection`] a [`SetProjection`] event can switch to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionKind {
+    Perspective,
+    Orthographic,
+}
+
+/// Switches `camera` to the given projection, matching the height visible at its focus point.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SetProjection {
+    pub camera: Entity,
+    pub kind: ProjectionKind,
+}
+
+// This system sends a `SetProjection` event flipping the projection of the camera under the
+// cursor when its toggle key is pressed.
+fn toggle_projection(
+    cameras: Query<(Entity, &Camera, &Projection, &CameraSettings)>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window>,
+    mut events: EventWriter<SetProjection>,
+) {
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    for (entity, camera, projection, settings) in &cameras {
+        if !settings.enabled
+            || !key_input.just_pressed(settings.key_toggle_projection)
+            || !is_hovered(camera, cursor_position)
+        {
+            continue;
+        }
+
+        let kind = match projection {
+            Projection::Orthographic(_) => ProjectionKind::Perspective,
+            _ => ProjectionKind::Orthographic,
+        };
+        events.write(SetProjection {
+            camera: entity,
+            kind,
+        });
+    }
+}
+
+fn set_projection(
+    mut events: EventReader<SetProjection>,
+    mut cameras: Query<(&mut Projection, &mut CameraSettings)>,
+) {
+    for event in events.read() {
+        let Ok((mut projection, mut settings)) = cameras.get_mut(event.camera) else {
+            continue;
+        };
+
+        match (&*projection, event.kind) {
+            (Projection::Perspective(perspective), ProjectionKind::Orthographic) => {
+                // The height of the frustum at the focus point becomes the height of the
+                // orthographic view volume.
+                let visible_height =
+                    2.0 * settings.current.orbit_distance * (perspective.fov / 2.0).tan();
+                let scale = (visible_height / settings.orthographic_viewport_height).clamp(
+                    settings.orthographic_zoom_range.start,
+                    settings.orthographic_zoom_range.end,
+                );
+
+                *projection = Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical {
+                        viewport_height: settings.orthographic_viewport_height,
+                    },
+                    scale,
+                    ..OrthographicProjection::default_3d()
+                });
+            }
+            (Projection::Orthographic(orthographic), ProjectionKind::Perspective) => {
+                let visible_height = match orthographic.scaling_mode {
+                    ScalingMode::FixedVertical { viewport_height } => viewport_height,
+                    _ => settings.orthographic_viewport_height,
+                } * orthographic.scale;
+
+                // Prefer keeping the camera where it is and picking the field of view that shows
+                // the same height at the focus point. If that is out of range, clamp it and move
+                // the camera instead.
+                let fov = (2.0 * (visible_height / (2.0 * settings.current.orbit_distance)).atan())
+                    .clamp(
+                        settings.perspective_zoom_range.start,
+                        settings.perspective_zoom_range.end,
+                    );
+                let orbit_distance = visible_height / (2.0 * (fov / 2.0).tan());
+                settings.orbit_distance = orbit_distance;
+                settings.current.orbit_distance = orbit_distance;
+
+                *projection = Projection::Perspective(PerspectiveProjection { fov, ..default() });
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestApp;
+
+    /// The height of the view volume at the camera's focus point.
+    fn visible_height(app: &TestApp) -> f32 {
+        match app.projection() {
+            Projection::Perspective(perspective) => {
+                2.0 * app.settings().current.orbit_distance * (perspective.fov / 2.0).tan()
+            }
+            Projection::Orthographic(orthographic) => match orthographic.scaling_mode {
+                ScalingMode::FixedVertical { viewport_height } => {
+                    viewport_height * orthographic.scale
+                }
+                scaling_mode => panic!("unexpected scaling mode {scaling_mode:?}"),
+            },
+            projection => panic!("unexpected projection {projection:?}"),
+        }
+    }
+
+    fn set_projection(app: &mut TestApp, kind: ProjectionKind) {
+        let camera = app.camera();
+        app.world_mut().send_event(SetProjection { camera, kind });
+        app.update();
+    }
+
+    #[test]
+    fn switching_there_and_back_keeps_the_view() {
+        let mut app = TestApp::looking_at_origin();
+        let height = visible_height(&app);
+        let orbit_distance = app.settings().orbit_distance;
+
+        set_projection(&mut app, ProjectionKind::Orthographic);
+        assert!(matches!(app.projection(), Projection::Orthographic(_)));
+        let orthographic_height = visible_height(&app);
+        assert!(
+            (orthographic_height - height).abs() < 1e-4,
+            "orthographic height {orthographic_height}, expected {height}"
+        );
+
+        set_projection(&mut app, ProjectionKind::Perspective);
+        assert!(matches!(app.projection(), Projection::Perspective(_)));
+        let perspective_height = visible_height(&app);
+        assert!(
+            (perspective_height - height).abs() < 1e-4,
+            "perspective height {perspective_height}, expected {height}"
+        );
+        let settings = app.settings();
+        assert!((settings.orbit_distance - orbit_distance).abs() < 1e-4);
+        assert!((settings.current.orbit_distance - orbit_distance).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+
+use crate::{Ground, orbit_camera::CameraSettings, transform_gizmo::AXIS_COLORS};
+
+/// Draws a reference grid on the ground, the world axes at the origin and a small widget in the
+/// corner of each viewport showing which way the world axes point.
+pub struct ReferenceGridPlugin;
+
+impl Plugin for ReferenceGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReferenceGrid>()
+            .init_gizmo_group::<OrientationGizmos>()
+            .add_systems(Startup, configure_orientation_gizmos)
+            .add_systems(
+                Update,
+                (
+                    toggle_grid,
+                    (draw_grid, draw_world_axes, draw_orientation_widget),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Reference grid [`Resource`].
+///
+/// The grid spacing follows the zoom of the first active [`CameraSettings`] camera, so there are
+/// always about [`target_cells`](ReferenceGrid::target_cells) cells across the view. Lines fade
+/// out with distance from the camera's focus, so the grid looks endless.
+#[derive(Resource, Debug)]
+pub struct ReferenceGrid {
+    pub show_grid: bool,
+    pub show_axes: bool,
+    pub show_orientation: bool,
+    /// Minor cells per major cell, which is also the factor the spacing changes by when zooming.
+    pub subdivisions: u32,
+    /// Roughly how many major cells span the view.
+    pub target_cells: f32,
+    /// Radius around the focus the grid fades out over, as a multiple of the view size.
+    pub fade_distance: f32,
+    pub minor_color: Color,
+    pub major_color: Color,
+    /// Length in metres of the axes drawn at the world origin.
+    pub axis_length: f32,
+    /// Length in logical pixels of the orientation widget's axes.
+    pub orientation_size: f32,
+    /// Distance in logical pixels from the widget to the bottom left corner of the viewport.
+    pub orientation_margin: f32,
+    /// [`KeyCode`] for showing and hiding the grid and axes.
+    pub key_toggle: KeyCode,
+}
+
+impl Default for ReferenceGrid {
+    fn default() -> Self {
+        Self {
+            show_grid: true,
+            show_axes: true,
+            show_orientation: true,
+            subdivisions: 10,
+            target_cells: 2.0,
+            fade_distance: 1.5,
+            minor_color: Color::srgba(1.0, 1.0, 1.0, 0.15),
+            major_color: Color::srgba(1.0, 1.0, 1.0, 0.4),
+            axis_length: 1.0,
+            orientation_size: 40.0,
+            orientation_margin: 60.0,
+            key_toggle: KeyCode::KeyH,
+        }
+    }
+}
+
+impl ReferenceGrid {
+    /// The minor line spacing for a view `view_size` metres across, and how far from 0 to 1 the
+    /// grid has faded towards the next coarser spacing.
+    fn spacing(&self, view_size: f32) -> (f32, f32) {
+        let base = self.subdivisions as f32;
+        // Zooming out by a factor of `base` moves up one level, which makes every major line a
+        // minor one; the fractional part blends between the two levels.
+        let level = (view_size / self.target_cells / base)
+            .max(f32::EPSILON)
+            .log(base);
+        (base.powf(level.floor()), level - level.floor())
+    }
+}
+
+/// Gizmos drawn on top of the scene.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct OrientationGizmos;
+
+/// Pieces each grid line is split into, so it can fade along its length.
+const FADE_SEGMENTS: u32 = 16;
+/// How far in front of the camera the orientation widget is drawn.
+const ORIENTATION_DEPTH: f32 = 1.0;
+
+fn configure_orientation_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<OrientationGizmos>();
+    config.depth_bias = -1.0;
+}
+
+fn toggle_grid(mut grid: ResMut<ReferenceGrid>, key_input: Res<ButtonInput<KeyCode>>) {
+    if key_input.just_pressed(grid.key_toggle) {
+        let show = !grid.show_grid;
+        grid.show_grid = show;
+        grid.show_axes = show;
+    }
+}
+
+fn draw_grid(
+    grid: Res<ReferenceGrid>,
+    cameras: Query<(&Camera, &Projection, &CameraSettings)>,
+    ground: Query<&GlobalTransform, With<Ground>>,
+    mut gizmos: Gizmos,
+) {
+    if !grid.show_grid || grid.subdivisions < 2 {
+        return;
+    }
+    let Some((_, projection, settings)) = cameras
+        .iter()
+        .filter(|(camera, ..)| camera.is_active)
+        .min_by_key(|(camera, ..)| camera.order)
+    else {
+        return;
+    };
+
+    let view_size = match projection {
+        Projection::Orthographic(orthographic) => {
+            orthographic.scale * settings.orthographic_viewport_height
+        }
+        _ => settings.current.orbit_distance,
+    };
+    let (spacing, blend) = grid.spacing(view_size);
+    let minor_color = grid
+        .minor_color
+        .with_alpha(grid.minor_color.alpha() * (1.0 - blend));
+    let fading_major_color = grid.major_color.mix(&grid.minor_color, blend);
+
+    let subdivisions = grid.subdivisions as i64;
+    let color = |line: i64| {
+        if line % (subdivisions * subdivisions) == 0 {
+            grid.major_color
+        } else if line % subdivisions == 0 {
+            fading_major_color
+        } else {
+            minor_color
+        }
+    };
+
+    let height = ground
+        .iter()
+        .next()
+        .map_or(0.0, |transform| transform.translation().y);
+    // Lift the grid off the ground mesh so the two do not fight over depth.
+    let center = settings
+        .current
+        .should_focus_at
+        .with_y(height + view_size * 1e-3);
+    let radius = view_size * grid.fade_distance;
+    let fade = |point: Vec3, color: Color| {
+        let fade = (1.0 - point.distance(center) / radius).clamp(0.0, 1.0);
+        color.with_alpha(color.alpha() * fade * fade)
+    };
+
+    // Lines along Z are at a fixed X, and lines along X at a fixed Z.
+    for (across, along, axis_color) in [
+        (Vec3::X, Vec3::Z, AXIS_COLORS[2]),
+        (Vec3::Z, Vec3::X, AXIS_COLORS[0]),
+    ] {
+        let offset = center.dot(across);
+        let first = ((offset - radius) / spacing).ceil() as i64;
+        let last = ((offset + radius) / spacing).floor() as i64;
+        for line in first..=last {
+            let color = match line {
+                0 if grid.show_axes => axis_color,
+                _ => color(line),
+            };
+            if color.alpha() < 0.01 {
+                continue;
+            }
+
+            let position = center.reject_from_normalized(across) + across * (line as f32 * spacing);
+            let half_length = (radius * radius - (position - center).length_squared())
+                .max(0.0)
+                .sqrt();
+            let start = position - along * half_length;
+            let step = along * (2.0 * half_length / FADE_SEGMENTS as f32);
+            for segment in 0..FADE_SEGMENTS {
+                let a = start + step * segment as f32;
+                let b = a + step;
+                gizmos.line_gradient(a, b, fade(a, color), fade(b, color));
+            }
+        }
+    }
+}
+
+fn draw_world_axes(grid: Res<ReferenceGrid>, mut gizmos: Gizmos) {
+    if !grid.show_axes {
+        return;
+    }
+    for (axis, color) in [Vec3::X, Vec3::Y, Vec3::Z].into_iter().zip(AXIS_COLORS) {
+        gizmos
+            .arrow(Vec3::ZERO, axis * grid.axis_length, color)
+            .with_tip_length(grid.axis_length * 0.2);
+    }
+}
+
+fn draw_orientation_widget(
+    grid: Res<ReferenceGrid>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut gizmos: Gizmos<OrientationGizmos>,
+) {
+    if !grid.show_orientation {
+        return;
+    }
+
+    for (camera, camera_transform) in &cameras {
+        if !camera.is_active {
+            continue;
+        }
+        let Some(viewport) = camera.logical_viewport_rect() else {
+            continue;
+        };
+        let corner = Vec2::new(
+            viewport.min.x + grid.orientation_margin,
+            viewport.max.y - grid.orientation_margin,
+        );
+        // Place the widget just in front of the camera under the corner, sized so that its axes
+        // are the same length on screen whatever the projection.
+        let point_at = |viewport_position: Vec2| {
+            camera
+                .viewport_to_world(camera_transform, viewport_position)
+                .ok()
+                .map(|ray| ray.get_point(ORIENTATION_DEPTH))
+        };
+        let (Some(center), Some(edge)) = (
+            point_at(corner),
+            point_at(corner + Vec2::X * grid.orientation_size),
+        ) else {
+            continue;
+        };
+        let length = center.distance(edge);
+
+        for (axis, color) in [Vec3::X, Vec3::Y, Vec3::Z].into_iter().zip(AXIS_COLORS) {
+            gizmos
+                .arrow(center, center + axis * length, color)
+                .with_tip_length(length * 0.25);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spacing_follows_the_orbit_distance() {
+        let grid = ReferenceGrid::default();
+        // Halfway between levels, in the logarithmic sense.
+        let halfway = 10f32.sqrt();
+        for (orbit_distance, expected_spacing) in [
+            (2.0 * halfway, 0.1),
+            (20.0 * halfway, 1.0),
+            (200.0 * halfway, 10.0),
+        ] {
+            let (spacing, blend) = grid.spacing(orbit_distance);
+            assert!(
+                (spacing - expected_spacing).abs() < 1e-5 && (blend - 0.5).abs() < 1e-4,
+                "{orbit_distance} m away: spacing {spacing}, blend {blend}"
+            );
+        }
+    }
+
+    #[test]
+    fn spacing_changes_level_where_the_blend_wraps() {
+        let grid = ReferenceGrid::default();
+        // The default view spans exactly `target_cells` major cells of 10 m, so ten times further
+        // out the minor lines become 10 m apart.
+        let level_change = CameraSettings::default().orbit_distance * 10.0;
+
+        let (spacing, blend) = grid.spacing(level_change * 0.999);
+        assert!((spacing - 1.0).abs() < 1e-5 && blend > 0.99);
+        let (spacing, blend) = grid.spacing(level_change * 1.001);
+        assert!((spacing - 10.0).abs() < 1e-5 && blend < 0.01);
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::{
+    app::{RunFixedMainLoop, RunFixedMainLoopSystem},
+    prelude::*,
+};
+
+/// Runs the simulation in [`FixedUpdate`] at a fixed tick rate, and interpolates the
+/// [`Transform`] of simulated entities between ticks so they move smoothly at any frame rate.
+///
+/// Systems that advance the simulation go in [`SimulationSet`], which [`SimulationControl`] can
+/// pause, single-step and slow down.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationControl>()
+            .configure_sets(FixedUpdate, SimulationSet.run_if(simulation_running))
+            .add_systems(Update, simulation_hotkeys)
+            .add_systems(
+                RunFixedMainLoop,
+                (
+                    (apply_tick_rate, restore_simulated_transforms)
+                        .chain()
+                        .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                    interpolate_transforms.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+                ),
+            )
+            .add_systems(
+                FixedFirst,
+                (
+                    advance_simulation_clock,
+                    store_previous_transforms.run_if(simulation_running),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedLast,
+                store_current_transforms.run_if(simulation_running),
+            );
+    }
+}
+
+/// Systems that advance the simulation by one tick.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SimulationSet;
+
+/// Simulation speed [`Resource`].
+///
+/// Slow motion skips ticks rather than shortening them, so the simulation gives the same
+/// results at any speed.
+#[derive(Resource, Debug)]
+pub struct SimulationControl {
+    /// Ticks per second.
+    pub tick_rate: f64,
+    pub paused: bool,
+    /// Fraction of the tick rate the simulation advances at, between zero and one.
+    pub speed: f32,
+    /// The slowest speed [`key_slower`](SimulationControl::key_slower) goes down to.
+    pub min_speed: f32,
+    /// [`KeyCode`] for pausing and resuming.
+    pub key_pause: KeyCode,
+    /// [`KeyCode`] for pausing and advancing a single tick.
+    pub key_step: KeyCode,
+    /// [`KeyCode`] for halving the speed.
+    pub key_slower: KeyCode,
+    /// [`KeyCode`] for doubling the speed, up to full speed.
+    pub key_faster: KeyCode,
+    /// How far the simulation is into its next tick, as a fraction of a tick.
+    progress: f32,
+    step_requested: bool,
+    /// Whether the current fixed tick advances the simulation.
+    running: bool,
+}
+
+impl Default for SimulationControl {
+    fn default() -> Self {
+        Self {
+            tick_rate: 64.0,
+            paused: false,
+            speed: 1.0,
+            min_speed: 1.0 / 16.0,
+            key_pause: KeyCode::KeyP,
+            key_step: KeyCode::Period,
+            key_slower: KeyCode::BracketLeft,
+            key_faster: KeyCode::BracketRight,
+            progress: 0.0,
+            step_requested: false,
+            running: false,
+        }
+    }
+}
+
+impl SimulationControl {
+    /// Advances a paused simulation by one tick.
+    pub fn step(&mut self) {
+        self.step_requested = true;
+    }
+
+    /// Whether the simulation advances in the current [`FixedUpdate`].
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// How far between its last two ticks the simulation should be shown, given how far the
+    /// fixed clock is into its next tick.
+    fn interpolation_fraction(&self, overstep_fraction: f32) -> f32 {
+        if self.paused {
+            // The last tick is shown in full, so single steps show all of their tick.
+            1.0
+        } else {
+            (self.progress + overstep_fraction * self.speed.clamp(0.0, 1.0)).min(1.0)
+        }
+    }
+}
+
+/// Run condition for systems that only run on ticks that advance the simulation.
+pub fn simulation_running(control: Res<SimulationControl>) -> bool {
+    control.is_running()
+}
+
+/// The [`Transform`]s an entity had after the last two simulation ticks.
+///
+/// Between ticks the entity is shown part of the way from one to the other. Anything that sets
+/// the [`Transform`] outside the simulation teleports the entity there.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct TransformInterpolation {
+    previous: Transform,
+    current: Transform,
+    /// The interpolated transform last shown, to tell whether something else has moved the
+    /// entity since.
+    rendered: Transform,
+}
+
+fn simulation_hotkeys(
+    mut control: ResMut<SimulationControl>,
+    key_input: Res<ButtonInput<KeyCode>>,
+) {
+    if key_input.just_pressed(control.key_pause) {
+        control.paused = !control.paused;
+    }
+    if key_input.just_pressed(control.key_step) {
+        control.paused = true;
+        control.step();
+    }
+    if key_input.just_pressed(control.key_slower) {
+        control.speed = (control.speed / 2.0).max(control.min_speed);
+        info!("Simulation speed: {}", control.speed);
+    }
+    if key_input.just_pressed(control.key_faster) {
+        control.speed = (control.speed * 2.0).min(1.0);
+        info!("Simulation speed: {}", control.speed);
+    }
+}
+
+fn apply_tick_rate(control: Res<SimulationControl>, mut time: ResMut<Time<Fixed>>) {
+    if control.is_changed() && control.tick_rate > 0.0 {
+        let timestep = time.timestep().as_secs_f64();
+        if (timestep * control.tick_rate - 1.0).abs() > 1e-9 {
+            time.set_timestep_hz(control.tick_rate);
+        }
+    }
+}
+
+fn advance_simulation_clock(mut control: ResMut<SimulationControl>) {
+    let step_requested = std::mem::take(&mut control.step_requested);
+    control.running = if control.paused {
+        step_requested
+    } else {
+        control.progress += control.speed.clamp(0.0, 1.0);
+        if control.progress >= 1.0 {
+            control.progress -= 1.0;
+            true
+        } else {
+            false
+        }
+    };
+}
+
+// This system puts simulated entities back where the simulation left them, so the next ticks
+// start from there rather than from where they were drawn.
+fn restore_simulated_transforms(
+    mut entities: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    for (mut transform, mut interpolation) in &mut entities {
+        if *transform == interpolation.rendered {
+            transform.set_if_neq(interpolation.current);
+        } else {
+            let teleported = *transform;
+            interpolation.set_if_neq(TransformInterpolation {
+                previous: teleported,
+                current: teleported,
+                rendered: teleported,
+            });
+        }
+    }
+}
+
+fn store_previous_transforms(mut entities: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut entities {
+        interpolation.previous = *transform;
+    }
+}
+
+fn store_current_transforms(mut entities: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut entities {
+        interpolation.current = *transform;
+    }
+}
+
+fn interpolate_transforms(
+    control: Res<SimulationControl>,
+    time: Res<Time<Fixed>>,
+    mut entities: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    let fraction = control.interpolation_fraction(time.overstep_fraction());
+    for (mut transform, mut interpolation) in &mut entities {
+        // Resuming carries on from the tick shown while paused, instead of going back into it.
+        if control.paused && interpolation.previous != interpolation.current {
+            interpolation.previous = interpolation.current;
+        }
+        let TransformInterpolation {
+            previous, current, ..
+        } = *interpolation;
+        let interpolated = Transform {
+            translation: previous.translation.lerp(current.translation, fraction),
+            rotation: previous.rotation.slerp(current.rotation, fraction),
+            scale: previous.scale.lerp(current.scale, fraction),
+        };
+        transform.set_if_neq(interpolated);
+        interpolation.rendered = interpolated;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    /// How many ticks the simulation has run.
+    #[derive(Resource, Default)]
+    struct Ticks(u32);
+
+    /// Moves every simulated entity one metre along X per tick.
+    fn move_along_x(
+        mut ticks: ResMut<Ticks>,
+        mut transforms: Query<&mut Transform, With<TransformInterpolation>>,
+    ) {
+        ticks.0 += 1;
+        for mut transform in &mut transforms {
+            transform.translation.x += 1.0;
+        }
+    }
+
+    #[test]
+    fn step_shows_exactly_one_more_tick() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, SimulationPlugin))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Ticks>()
+            // Frames are longer than ticks, so every frame runs at least one tick.
+            .insert_resource(TimeUpdateStrategy::ManualDuration(
+                Duration::from_secs(1) / 60,
+            ))
+            .add_systems(FixedUpdate, move_along_x.in_set(SimulationSet));
+        let entity = app
+            .world_mut()
+            .spawn((Transform::default(), TransformInterpolation::default()))
+            .id();
+        let shown_x = |app: &App| app.world().get::<Transform>(entity).unwrap().translation.x;
+        let ticks = |app: &App| app.world().resource::<Ticks>().0 as f32;
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_ne!(
+            shown_x(&app),
+            shown_x(&app).round(),
+            "should be between ticks"
+        );
+
+        app.world_mut().resource_mut::<SimulationControl>().paused = true;
+        app.update();
+        let paused_x = shown_x(&app);
+        assert_eq!(paused_x, ticks(&app), "should show the last tick");
+        for _ in 0..5 {
+            app.update();
+            assert_eq!(shown_x(&app), paused_x);
+        }
+
+        app.world_mut().resource_mut::<SimulationControl>().step();
+        for _ in 0..5 {
+            app.update();
+            assert_eq!(shown_x(&app), paused_x + 1.0);
+            assert_eq!(shown_x(&app), ticks(&app));
+        }
+    }
+}
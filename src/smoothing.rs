@@ -0,0 +1,60 @@
+use bevy::math::{StableInterpolate, Vec3};
+use std::f32::consts::LN_2;
+
+/// Moves `current` exponentially towards `target` so that the remaining distance halves every
+/// `half_life` seconds.
+///
+/// Unlike multiplying by a constant factor every frame, this depends only on the elapsed time,
+/// so the same motion is produced at 30 and at 144 FPS. A `half_life` of zero or less snaps
+/// straight to `target`.
+pub fn smooth_towards<T: StableInterpolate>(current: &mut T, target: &T, half_life: f32, dt: f32) {
+    if half_life <= 0.0 {
+        *current = target.clone();
+    } else {
+        current.smooth_nudge(target, LN_2 / half_life, dt);
+    }
+}
+
+/// How far something travels in `dt` seconds while its velocity is moved from `current`
+/// towards `target` by [`smooth_towards`].
+///
+/// This integrates the eased velocity exactly. Multiplying the new velocity by `dt` instead
+/// would cover more ground per frame at low frame rates.
+pub fn smoothed_displacement(current: Vec3, target: Vec3, half_life: f32, dt: f32) -> Vec3 {
+    if half_life <= 0.0 {
+        return target * dt;
+    }
+    let decay_rate = LN_2 / half_life;
+    target * dt + (current - target) * (1.0 - (-decay_rate * dt).exp()) / decay_rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_long_step_matches_two_short_ones() {
+        let (start, target, half_life, dt) = (Vec3::new(4.0, -2.0, 1.0), Vec3::X, 0.2, 0.05);
+
+        let mut once = start;
+        smooth_towards(&mut once, &target, half_life, 2.0 * dt);
+        let mut twice = start;
+        smooth_towards(&mut twice, &target, half_life, dt);
+        smooth_towards(&mut twice, &target, half_life, dt);
+
+        assert!(once.abs_diff_eq(twice, 1e-6), "{once} != {twice}");
+    }
+
+    #[test]
+    fn displacement_does_not_depend_on_step_length() {
+        let (start, target, half_life, dt) = (Vec3::new(4.0, -2.0, 1.0), Vec3::X, 0.2, 0.05);
+
+        let once = smoothed_displacement(start, target, half_life, 2.0 * dt);
+        let mut velocity = start;
+        let mut twice = smoothed_displacement(velocity, target, half_life, dt);
+        smooth_towards(&mut velocity, &target, half_life, dt);
+        twice += smoothed_displacement(velocity, target, half_life, dt);
+
+        assert!(once.abs_diff_eq(twice, 1e-6), "{once} != {twice}");
+    }
+}
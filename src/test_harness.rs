@@ -0,0 +1,354 @@
+//! A headless [`App`] for testing the camera systems, with a fake window and scripted input.
+
+use bevy::{
+    gizmos::GizmoPlugin,
+    input::{
+        ButtonState, InputPlugin,
+        gamepad::{
+            GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
+            RawGamepadButtonChangedEvent, RawGamepadEvent,
+        },
+        keyboard::{Key, KeyboardInput, NativeKey},
+        mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
+    },
+    prelude::*,
+    render::{
+        camera::{CameraUpdateSystem, ManualTextureViews, camera_system},
+        render_resource::Shader,
+    },
+    time::TimeUpdateStrategy,
+    window::{ExitCondition, PrimaryWindow, WindowResolution},
+};
+use std::time::Duration;
+
+use crate::{
+    Ground,
+    camera_collision::CameraCollisionPlugin,
+    camera_controller::CameraControllerPlugin,
+    camera_mode::CameraModePlugin,
+    cursor_grab::CursorGrabPlugin,
+    drag::DragPlugin,
+    framing::FramingPlugin,
+    input_map::InputMap,
+    input_replay::{InputRecording, InputReplay, InputReplayPlugin, ReplayMismatch},
+    orbit_camera::{CameraSettings, OrbitCameraPlugin},
+    physics::PhysicsPlugin,
+    picking::ScenePickingPlugin,
+    projection_switch::ProjectionSwitchPlugin,
+    simulation::SimulationPlugin,
+    transform_gizmo::TransformGizmo,
+    view_snap::ViewSnapPlugin,
+};
+
+/// Size of the fake window, in logical pixels.
+pub const WINDOW_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+/// How long every frame takes, unless [changed](TestApp::set_frame_time).
+pub const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// An app running the [`OrbitCameraPlugin`] and [`ProjectionSwitchPlugin`] on one camera,
+/// without a renderer or real input.
+///
+/// Input is sent as events, so it goes through the same input systems as in the real app and
+/// takes effect on the next [`update`](TestApp::update). Every frame takes [`FRAME_TIME`]
+/// unless [changed](TestApp::set_frame_time).
+///
+/// The [`CameraControllerPlugin`], [`CameraModePlugin`], [`CameraCollisionPlugin`],
+/// [`FramingPlugin`] and [`ViewSnapPlugin`] run as well, for tests that give the camera the
+/// components they act on or send them events. So do the [`PhysicsPlugin`], picking and
+/// dragging, for tests that spawn bodies to click on.
+pub struct TestApp {
+    app: App,
+    window: Entity,
+    camera: Entity,
+    frame_time: Duration,
+}
+
+impl TestApp {
+    /// A camera at `camera_transform` orbiting the origin above a [`Ground`] at the origin, with
+    /// the cursor in the middle of the window.
+    ///
+    /// The first frame has already run, so the camera is initialized.
+    pub fn new(camera_transform: Transform) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            // The gizmo plugin loads its shaders even without a renderer.
+            .init_asset::<Shader>();
+        app.add_plugins((
+            TransformPlugin,
+            InputPlugin,
+            WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: WindowResolution::new(WINDOW_SIZE.x, WINDOW_SIZE.y),
+                    ..default()
+                }),
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            },
+            GizmoPlugin,
+            CursorGrabPlugin,
+            OrbitCameraPlugin,
+            CameraControllerPlugin,
+            CameraModePlugin,
+            CameraCollisionPlugin,
+            FramingPlugin,
+            ProjectionSwitchPlugin,
+            ViewSnapPlugin,
+            InputReplayPlugin,
+        ))
+        .add_plugins((
+            SimulationPlugin,
+            PhysicsPlugin,
+            ScenePickingPlugin,
+            DragPlugin,
+        ))
+        .init_asset::<Image>()
+        .init_asset::<Mesh>()
+        .init_resource::<ManualTextureViews>()
+        // The built-in bindings, rather than whatever the asset file says.
+        .init_resource::<InputMap>()
+        .init_resource::<TransformGizmo>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+        // Without a renderer, nothing else works out the camera's viewport and projection.
+        .add_systems(PostUpdate, camera_system.in_set(CameraUpdateSystem));
+
+        app.world_mut().spawn((Transform::default(), Ground));
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera3d::default(),
+                camera_transform,
+                CameraSettings::default(),
+            ))
+            .id();
+        let window = app
+            .world_mut()
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(app.world())
+            .expect("WindowPlugin spawns a primary window");
+
+        let mut test_app = Self {
+            app,
+            window,
+            camera,
+            frame_time: FRAME_TIME,
+        };
+        test_app.set_cursor(Some(WINDOW_SIZE / 2.0));
+        test_app.update();
+        test_app
+    }
+
+    /// A camera 10 m up and 10 m back from the origin, looking at it.
+    pub fn looking_at_origin() -> Self {
+        Self::new(Transform::from_xyz(0.0, 10.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y))
+    }
+
+    /// Runs one frame.
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Runs `frames` frames.
+    pub fn run(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
+    /// Runs as many frames as fit in `duration`.
+    pub fn run_for(&mut self, duration: Duration) {
+        self.run((duration.as_secs_f64() / self.frame_time.as_secs_f64()).round() as usize);
+    }
+
+    /// Lets the camera's smoothing catch up with its target.
+    pub fn settle(&mut self) {
+        self.run_for(FRAME_TIME * 120);
+    }
+
+    /// Makes every following frame take `frame_time`.
+    pub fn set_frame_time(&mut self, frame_time: Duration) {
+        self.frame_time = frame_time;
+        self.world_mut()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Puts the cursor at `position` in logical pixels, without moving the mouse.
+    pub fn set_cursor(&mut self, position: Option<Vec2>) {
+        let window = self.window;
+        let mut window = self.world_mut().get_mut::<Window>(window).unwrap();
+        window.set_cursor_position(position);
+    }
+
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.world()
+            .get::<Window>(self.window)
+            .unwrap()
+            .cursor_position()
+    }
+
+    /// Moves the mouse by `delta`, taking the cursor along if it is in the window.
+    pub fn move_mouse(&mut self, delta: Vec2) {
+        if let Some(cursor) = self.cursor() {
+            self.set_cursor(Some(cursor + delta));
+        }
+        self.world_mut().send_event(MouseMotion { delta });
+    }
+
+    pub fn press_key(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Released);
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) {
+        let window = self.window;
+        self.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window,
+        });
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) {
+        self.send_mouse_button(button, ButtonState::Pressed);
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) {
+        self.send_mouse_button(button, ButtonState::Released);
+    }
+
+    fn send_mouse_button(&mut self, button: MouseButton, state: ButtonState) {
+        let window = self.window;
+        self.world_mut().send_event(MouseButtonInput {
+            button,
+            state,
+            window,
+        });
+    }
+
+    /// Scrolls the mouse wheel by `lines`, positive values scrolling up.
+    pub fn scroll(&mut self, lines: f32) {
+        let window = self.window;
+        self.world_mut().send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.0,
+            y: lines,
+            window,
+        });
+    }
+
+    /// Plugs in a gamepad, which is connected from the next frame on.
+    pub fn connect_gamepad(&mut self) -> Entity {
+        let gamepad = self.world_mut().spawn_empty().id();
+        self.world_mut().send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected {
+                name: "Test gamepad".to_string(),
+                vendor_id: None,
+                product_id: None,
+            },
+        ));
+        gamepad
+    }
+
+    /// Moves `gamepad`'s `axis` to `value`, from -1 to 1, before the gamepad's own dead zone.
+    pub fn set_gamepad_axis(&mut self, gamepad: Entity, axis: GamepadAxis, value: f32) {
+        self.world_mut()
+            .send_event(RawGamepadEvent::Axis(RawGamepadAxisChangedEvent::new(
+                gamepad, axis, value,
+            )));
+    }
+
+    /// Pushes `gamepad`'s `button` to `value`, from 0 for released to 1 for fully pressed.
+    pub fn set_gamepad_button(&mut self, gamepad: Entity, button: GamepadButton, value: f32) {
+        self.world_mut()
+            .send_event(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(
+                gamepad, button, value,
+            )));
+    }
+
+    /// Holds `button` while moving the mouse by `delta` in equal steps over `frames` frames,
+    /// then lets go.
+    pub fn drag(&mut self, button: MouseButton, delta: Vec2, frames: usize) {
+        self.press_mouse(button);
+        self.update();
+        for _ in 0..frames {
+            self.move_mouse(delta / frames as f32);
+            self.update();
+        }
+        self.release_mouse(button);
+        self.update();
+    }
+
+    /// Plays `recording` back from the current frame, returning where the camera first left the
+    /// recorded path.
+    ///
+    /// Only the camera systems run here, so recordings of the full app replay as long as what
+    /// they do does not depend on the rest of it.
+    pub fn replay(&mut self, recording: InputRecording) -> Result<(), Box<ReplayMismatch>> {
+        self.world_mut()
+            .insert_resource(InputReplay::new(recording));
+        while !self.world().resource::<InputReplay>().is_finished() {
+            self.update();
+        }
+        let replay = self
+            .world_mut()
+            .remove_resource::<InputReplay>()
+            .expect("replay is only removed here");
+        let frame_time = self.frame_time;
+        self.set_frame_time(frame_time);
+        match replay.mismatch() {
+            Some(mismatch) => Err(Box::new(mismatch.clone())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn camera(&self) -> Entity {
+        self.camera
+    }
+
+    pub fn transform(&self) -> Transform {
+        *self.world().get::<Transform>(self.camera).unwrap()
+    }
+
+    pub fn projection(&self) -> &Projection {
+        self.world().get::<Projection>(self.camera).unwrap()
+    }
+
+    pub fn settings(&self) -> &CameraSettings {
+        self.world().get::<CameraSettings>(self.camera).unwrap()
+    }
+
+    pub fn settings_mut(&mut self) -> Mut<'_, CameraSettings> {
+        let camera = self.camera;
+        self.world_mut().get_mut::<CameraSettings>(camera).unwrap()
+    }
+
+    /// The ray through `viewport_position`, as the camera was drawn last frame.
+    pub fn cursor_ray(&self, viewport_position: Vec2) -> Option<Ray3d> {
+        let camera = self.world().get::<Camera>(self.camera)?;
+        let camera_transform = self.world().get::<GlobalTransform>(self.camera)?;
+        camera
+            .viewport_to_world(camera_transform, viewport_position)
+            .ok()
+    }
+
+    /// The point on the ground under `viewport_position`, as the camera was drawn last frame.
+    pub fn ground_point(&self, viewport_position: Vec2) -> Option<Vec3> {
+        let ray = self.cursor_ray(viewport_position)?;
+        let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+        Some(ray.get_point(distance))
+    }
+}
@@ -0,0 +1,808 @@
+use bevy::{input::InputSystem, prelude::*};
+use std::f32::consts::PI;
+
+use crate::{
+    Ground,
+    cursor_grab::CursorGrab,
+    input_map::{Action, ActionInput},
+    orbit_camera::is_hovered,
+    physics::{Collider, RigidBody, Velocity},
+    picking::Clicked,
+};
+
+/// Translate, rotate and scale handles for the entity selected with [`Action::Select`].
+///
+/// Clicking an entity selects the root of its hierarchy; clicking the [`Ground`] clears the
+/// selection. The selection is expected to be a root entity, so its [`Transform`] is in world
+/// space.
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TransformGizmo>()
+            // Handles are grabbed before `Update`, so nothing else acts on the same click.
+            .add_systems(PreUpdate, start_gizmo_drag.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    gizmo_hotkeys,
+                    select_clicked,
+                    update_gizmo_drag,
+                    hover_handles,
+                    draw_gizmo,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Which handles the [`TransformGizmo`] shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    /// Not available for physics bodies and colliders, which keep the size they were built
+    /// with, so no handles are shown for them.
+    Scale,
+}
+
+/// The axes translate and rotate handles follow. Scale handles always follow the entity's own
+/// axes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoSpace {
+    #[default]
+    World,
+    Local,
+}
+
+/// Transform gizmo [`Resource`].
+#[derive(Resource, Debug)]
+pub struct TransformGizmo {
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    /// Length of the handles as a fraction of their distance from the camera, so they stay the
+    /// same size on screen.
+    pub size: f32,
+    /// Grid spacing in metres that translation snaps to.
+    pub translate_snap: f32,
+    /// Angle in radians that rotation snaps to.
+    pub rotate_snap: f32,
+    /// Increment that scale snaps to.
+    pub scale_snap: f32,
+    /// [`KeyCode`] for translate handles.
+    pub key_translate: KeyCode,
+    /// [`KeyCode`] for rotate handles.
+    pub key_rotate: KeyCode,
+    /// [`KeyCode`] for scale handles.
+    pub key_scale: KeyCode,
+    /// [`KeyCode`] for switching between world and local axes.
+    pub key_toggle_space: KeyCode,
+    /// [`KeyCode`] to hold for snapping.
+    pub key_snap: KeyCode,
+    /// [`KeyCode`] for clearing the selection.
+    pub key_deselect: KeyCode,
+    selected: Option<Entity>,
+    /// The handle under the cursor, and the camera it is seen through.
+    hovered: Option<(GizmoHandle, Entity)>,
+    active: Option<ActiveDrag>,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::default(),
+            space: GizmoSpace::default(),
+            size: 0.15,
+            translate_snap: 0.5,
+            rotate_snap: PI / 12.0,
+            scale_snap: 0.25,
+            key_translate: KeyCode::KeyG,
+            key_rotate: KeyCode::KeyR,
+            key_scale: KeyCode::KeyT,
+            key_toggle_space: KeyCode::KeyL,
+            key_snap: KeyCode::ControlLeft,
+            key_deselect: KeyCode::Escape,
+            selected: None,
+            hovered: None,
+            active: None,
+        }
+    }
+}
+
+impl TransformGizmo {
+    /// The entity the handles are attached to.
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    pub fn select(&mut self, entity: Option<Entity>) {
+        self.selected = entity;
+        self.hovered = None;
+        self.active = None;
+    }
+
+    /// Whether the cursor is over a handle, so a click would grab it.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered.is_some()
+    }
+
+    /// Whether a handle is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.active.is_some()
+    }
+
+    fn handles(&self, scalable: bool) -> &'static [GizmoHandle] {
+        use GizmoHandle::*;
+        match self.mode {
+            GizmoMode::Translate => &[Axis(0), Axis(1), Axis(2), Plane(0), Plane(1), Plane(2)],
+            GizmoMode::Rotate => &[Ring(0), Ring(1), Ring(2)],
+            GizmoMode::Scale if scalable => &[Axis(0), Axis(1), Axis(2), Center],
+            GizmoMode::Scale => &[],
+        }
+    }
+
+    fn axes(&self, transform: &Transform) -> [Vec3; 3] {
+        match (self.mode, self.space) {
+            (GizmoMode::Scale, _) | (_, GizmoSpace::Local) => [
+                transform.rotation * Vec3::X,
+                transform.rotation * Vec3::Y,
+                transform.rotation * Vec3::Z,
+            ],
+            (_, GizmoSpace::World) => [Vec3::X, Vec3::Y, Vec3::Z],
+        }
+    }
+}
+
+/// A part of the gizmo that can be dragged. The index is that of the axis it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GizmoHandle {
+    /// Moves or scales along the axis.
+    Axis(usize),
+    /// Moves in the plane the axis is normal to.
+    Plane(usize),
+    /// Rotates about the axis.
+    Ring(usize),
+    /// Scales uniformly.
+    Center,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveDrag {
+    handle: GizmoHandle,
+    camera: Entity,
+    /// The transform of the selection when the handle was grabbed.
+    start: Transform,
+    axes: [Vec3; 3],
+    /// Where the handle was grabbed, on the line or plane it moves in.
+    grab_point: Vec3,
+    /// The direction the camera looked in when the handle was grabbed.
+    view_direction: Vec3,
+}
+
+/// Colours of the X, Y and Z axes, shared by everything that draws axes.
+pub const AXIS_COLORS: [Color; 3] = [
+    Color::srgb(0.9, 0.2, 0.2),
+    Color::srgb(0.2, 0.8, 0.2),
+    Color::srgb(0.2, 0.4, 0.9),
+];
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+
+/// Distance from the gizmo origin to the centre of the plane handles, relative to its size.
+const PLANE_HANDLE_OFFSET: f32 = 0.3;
+/// Half the side of the plane handles, relative to the gizmo size.
+const PLANE_HANDLE_HALF_SIZE: f32 = 0.08;
+/// How close to a handle the cursor ray has to pass, relative to the gizmo size.
+const PICK_TOLERANCE: f32 = 0.06;
+
+/// Entities the physics reads the shape of.
+type InPhysics = Or<(With<RigidBody>, With<Collider>)>;
+
+/// Whether `entity` can be scaled: neither it nor anything under it takes part in the physics,
+/// whose shapes and masses do not follow [`Transform::scale`].
+fn is_scalable(
+    entity: Entity,
+    children: &Query<&Children>,
+    physics: &Query<(), InPhysics>,
+) -> bool {
+    !std::iter::once(entity)
+        .chain(children.iter_descendants(entity))
+        .any(|entity| physics.contains(entity))
+}
+
+/// The camera under the cursor and the ray through the cursor from it.
+fn cursor_ray(
+    window: &Window,
+    cameras: &Query<(Entity, &Camera, &GlobalTransform)>,
+    camera: Option<Entity>,
+) -> Option<(Entity, Ray3d)> {
+    let cursor_position = window.cursor_position()?;
+    let (entity, camera, camera_transform) = match camera {
+        Some(camera) => cameras.get(camera).ok()?,
+        None => cameras
+            .iter()
+            .find(|(_, camera, _)| camera.is_active && is_hovered(camera, cursor_position))?,
+    };
+    let ray = camera
+        .viewport_to_world(camera_transform, cursor_position)
+        .ok()?;
+    Some((entity, ray))
+}
+
+/// The length of the handles seen from `camera_position`.
+fn gizmo_size(settings: &TransformGizmo, origin: Vec3, camera_position: Vec3) -> f32 {
+    origin.distance(camera_position) * settings.size
+}
+
+/// The parameter along the line through `origin` along the unit vector `axis` of the point
+/// closest to `ray`, or `None` if they are parallel.
+fn closest_on_axis(origin: Vec3, axis: Vec3, ray: Ray3d) -> Option<f32> {
+    let direction = ray.direction.as_vec3();
+    let offset = origin - ray.origin;
+    let b = axis.dot(direction);
+    let denominator = 1.0 - b * b;
+    if denominator < 1e-6 {
+        return None;
+    }
+    Some((b * direction.dot(offset) - axis.dot(offset)) / denominator)
+}
+
+/// The distance from `point` to `ray`, and how far along the ray it is.
+fn ray_distance(ray: Ray3d, point: Vec3) -> (f32, f32) {
+    let along = (point - ray.origin).dot(*ray.direction);
+    (point.distance(ray.get_point(along)), along)
+}
+
+/// Where `ray` meets the line or plane `handle` moves in.
+fn constraint_point(
+    handle: GizmoHandle,
+    origin: Vec3,
+    axes: &[Vec3; 3],
+    ray: Ray3d,
+    view_direction: Vec3,
+) -> Option<Vec3> {
+    let plane_point = |normal: Vec3| {
+        ray.intersect_plane(origin, InfinitePlane3d::new(normal))
+            .map(|distance| ray.get_point(distance))
+    };
+    match handle {
+        GizmoHandle::Axis(i) => closest_on_axis(origin, axes[i], ray).map(|t| origin + axes[i] * t),
+        GizmoHandle::Plane(i) | GizmoHandle::Ring(i) => plane_point(axes[i]),
+        GizmoHandle::Center => plane_point(view_direction),
+    }
+}
+
+/// How far along `ray` it hits `handle`, if it does.
+fn pick_handle(
+    handle: GizmoHandle,
+    origin: Vec3,
+    axes: &[Vec3; 3],
+    size: f32,
+    ray: Ray3d,
+) -> Option<f32> {
+    let tolerance = size * PICK_TOLERANCE;
+    let plane_hit = |normal: Vec3| {
+        let distance = ray.intersect_plane(origin, InfinitePlane3d::new(normal))?;
+        Some((distance, ray.get_point(distance) - origin))
+    };
+    match handle {
+        GizmoHandle::Axis(i) => {
+            let t = closest_on_axis(origin, axes[i], ray)?.clamp(0.0, size);
+            let (distance, along) = ray_distance(ray, origin + axes[i] * t);
+            (distance < tolerance).then_some(along)
+        }
+        GizmoHandle::Plane(i) => {
+            let (distance, offset) = plane_hit(axes[i])?;
+            let center = size * PLANE_HANDLE_OFFSET;
+            let half_size = size * PLANE_HANDLE_HALF_SIZE;
+            [axes[(i + 1) % 3], axes[(i + 2) % 3]]
+                .iter()
+                .all(|axis| (offset.dot(*axis) - center).abs() < half_size)
+                .then_some(distance)
+        }
+        GizmoHandle::Ring(i) => {
+            let (distance, offset) = plane_hit(axes[i])?;
+            ((offset.length() - size).abs() < tolerance).then_some(distance)
+        }
+        GizmoHandle::Center => {
+            let (distance, along) = ray_distance(ray, origin);
+            (distance < tolerance * 2.0).then_some(along)
+        }
+    }
+}
+
+fn snap(value: f32, increment: f32) -> f32 {
+    if increment > 0.0 {
+        (value / increment).round() * increment
+    } else {
+        value
+    }
+}
+
+/// The transform the selection gets when `drag` has been moved to `point`.
+fn dragged_transform(
+    settings: &TransformGizmo,
+    drag: &ActiveDrag,
+    point: Vec3,
+    snapping: bool,
+) -> Transform {
+    let mut transform = drag.start;
+    let origin = drag.start.translation;
+    let axes = &drag.axes;
+
+    match (settings.mode, drag.handle) {
+        (GizmoMode::Translate, GizmoHandle::Axis(_) | GizmoHandle::Plane(_)) => {
+            let moved = match drag.handle {
+                GizmoHandle::Axis(i) => [Some(i), None],
+                GizmoHandle::Plane(i) => [Some((i + 1) % 3), Some((i + 2) % 3)],
+                _ => [None, None],
+            };
+            let delta = point - drag.grab_point;
+            for i in moved.into_iter().flatten() {
+                let mut distance = delta.dot(axes[i]);
+                if snapping {
+                    distance = match settings.space {
+                        // Land on the world grid rather than moving by whole grid steps.
+                        GizmoSpace::World => {
+                            snap(origin[i] + distance, settings.translate_snap) - origin[i]
+                        }
+                        GizmoSpace::Local => snap(distance, settings.translate_snap),
+                    };
+                }
+                transform.translation += axes[i] * distance;
+            }
+        }
+        (GizmoMode::Rotate, GizmoHandle::Ring(i)) => {
+            let from = drag.grab_point - origin;
+            let to = point - origin;
+            let mut angle = from.cross(to).dot(axes[i]).atan2(from.dot(to));
+            if snapping {
+                angle = snap(angle, settings.rotate_snap);
+            }
+            transform.rotation = Quat::from_axis_angle(axes[i], angle) * drag.start.rotation;
+        }
+        (GizmoMode::Scale, GizmoHandle::Axis(i)) => {
+            let from = (drag.grab_point - origin).dot(axes[i]);
+            if from.abs() > f32::EPSILON {
+                let mut scale = drag.start.scale[i] * (point - origin).dot(axes[i]) / from;
+                if snapping {
+                    scale = snap(scale, settings.scale_snap);
+                }
+                transform.scale[i] = scale;
+            }
+        }
+        (GizmoMode::Scale, GizmoHandle::Center) => {
+            let from = drag.grab_point.distance(origin);
+            if from > f32::EPSILON {
+                let mut factor = point.distance(origin) / from;
+                if snapping {
+                    factor = snap(factor, settings.scale_snap);
+                }
+                transform.scale = drag.start.scale * factor;
+            }
+        }
+        _ => (),
+    }
+    transform
+}
+
+fn gizmo_hotkeys(mut settings: ResMut<TransformGizmo>, key_input: Res<ButtonInput<KeyCode>>) {
+    if settings.is_dragging() {
+        return;
+    }
+
+    if key_input.just_pressed(settings.key_translate) {
+        settings.mode = GizmoMode::Translate;
+    } else if key_input.just_pressed(settings.key_rotate) {
+        settings.mode = GizmoMode::Rotate;
+    } else if key_input.just_pressed(settings.key_scale) {
+        settings.mode = GizmoMode::Scale;
+    }
+    if key_input.just_pressed(settings.key_toggle_space) {
+        settings.space = match settings.space {
+            GizmoSpace::World => GizmoSpace::Local,
+            GizmoSpace::Local => GizmoSpace::World,
+        };
+    }
+    if key_input.just_pressed(settings.key_deselect) {
+        settings.select(None);
+    }
+}
+
+fn select_clicked(
+    mut settings: ResMut<TransformGizmo>,
+    mut clicks: EventReader<Clicked>,
+    parents: Query<&ChildOf>,
+    ground: Query<(), With<Ground>>,
+) {
+    for click in clicks.read() {
+        // The click went to a handle, not to what is behind it.
+        if settings.is_hovered() || settings.is_dragging() {
+            continue;
+        }
+        let root = parents
+            .iter_ancestors(click.hit.entity)
+            .last()
+            .unwrap_or(click.hit.entity);
+        let selected = (!ground.contains(root)).then_some(root);
+        settings.select(selected);
+    }
+}
+
+fn hover_handles(
+    mut settings: ResMut<TransformGizmo>,
+    window: Single<&Window>,
+    cursor_grab: Res<CursorGrab>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    transforms: Query<&Transform>,
+    children: Query<&Children>,
+    physics: Query<(), InPhysics>,
+) {
+    if settings.is_dragging() {
+        return;
+    }
+    settings.hovered = None;
+    let Some((selected, transform)) = settings
+        .selected
+        .and_then(|entity| Some((entity, transforms.get(entity).ok()?)))
+    else {
+        return;
+    };
+    if cursor_grab.is_grabbed() {
+        return;
+    }
+    let Some((camera, ray)) = cursor_ray(&window, &cameras, None) else {
+        return;
+    };
+
+    let origin = transform.translation;
+    let axes = settings.axes(transform);
+    let size = gizmo_size(&settings, origin, ray.origin);
+    settings.hovered = settings
+        .handles(is_scalable(selected, &children, &physics))
+        .iter()
+        .filter_map(|&handle| {
+            pick_handle(handle, origin, &axes, size, ray).map(|distance| (handle, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(handle, _)| (handle, camera));
+}
+
+fn start_gizmo_drag(
+    mut settings: ResMut<TransformGizmo>,
+    actions: ActionInput,
+    window: Single<&Window>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    transforms: Query<&Transform>,
+) {
+    if settings.is_dragging() || !actions.just_pressed(Action::Select) {
+        return;
+    }
+    let (Some((handle, camera)), Some(selected)) = (settings.hovered, settings.selected) else {
+        return;
+    };
+    let (Ok(transform), Some((_, ray))) = (
+        transforms.get(selected),
+        cursor_ray(&window, &cameras, Some(camera)),
+    ) else {
+        return;
+    };
+
+    let axes = settings.axes(transform);
+    let Some(grab_point) =
+        constraint_point(handle, transform.translation, &axes, ray, *ray.direction)
+    else {
+        return;
+    };
+    settings.active = Some(ActiveDrag {
+        handle,
+        camera,
+        start: *transform,
+        axes,
+        grab_point,
+        view_direction: *ray.direction,
+    });
+}
+
+// This system moves the selection with the grabbed handle, and lets go of the handle once the
+// button is released.
+fn update_gizmo_drag(
+    mut settings: ResMut<TransformGizmo>,
+    actions: ActionInput,
+    key_input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    mut transforms: Query<(&mut Transform, Option<&mut Velocity>)>,
+) {
+    let (Some(drag), Some(selected)) = (settings.active, settings.selected) else {
+        return;
+    };
+    if !actions.pressed(Action::Select) {
+        settings.active = None;
+        return;
+    }
+    let Ok((mut transform, velocity)) = transforms.get_mut(selected) else {
+        settings.active = None;
+        return;
+    };
+    // A body being placed by hand should not keep whatever speed it had.
+    if let Some(mut velocity) = velocity {
+        velocity.set_if_neq(Velocity::default());
+    }
+
+    let Some((_, ray)) = cursor_ray(&window, &cameras, Some(drag.camera)) else {
+        return;
+    };
+    let Some(point) = constraint_point(
+        drag.handle,
+        drag.start.translation,
+        &drag.axes,
+        ray,
+        drag.view_direction,
+    ) else {
+        return;
+    };
+    let snapping = key_input.pressed(settings.key_snap);
+    transform.set_if_neq(dragged_transform(&settings, &drag, point, snapping));
+}
+
+fn draw_gizmo(
+    settings: Res<TransformGizmo>,
+    window: Single<&Window>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    transforms: Query<&Transform>,
+    children: Query<&Children>,
+    physics: Query<(), InPhysics>,
+    mut gizmos: Gizmos,
+) {
+    let Some((selected, transform)) = settings
+        .selected
+        .and_then(|entity| Some((entity, transforms.get(entity).ok()?)))
+    else {
+        return;
+    };
+    // Size the handles for the camera being used, or any camera if the cursor is elsewhere.
+    let camera = settings
+        .active
+        .map(|drag| drag.camera)
+        .or(settings.hovered.map(|(_, camera)| camera));
+    let Some(camera_position) = cursor_ray(&window, &cameras, camera)
+        .map(|(_, ray)| ray.origin)
+        .or_else(|| {
+            cameras
+                .iter()
+                .find(|(_, camera, _)| camera.is_active)
+                .map(|(_, _, transform)| transform.translation())
+        })
+    else {
+        return;
+    };
+
+    let origin = transform.translation;
+    let axes = match settings.active {
+        Some(drag) => drag.axes,
+        None => settings.axes(transform),
+    };
+    let size = gizmo_size(&settings, origin, camera_position);
+    let highlighted = settings
+        .active
+        .map(|drag| drag.handle)
+        .or(settings.hovered.map(|(handle, _)| handle));
+
+    for &handle in settings.handles(is_scalable(selected, &children, &physics)) {
+        let color = match handle {
+            _ if highlighted == Some(handle) => HIGHLIGHT_COLOR,
+            GizmoHandle::Axis(i) | GizmoHandle::Plane(i) | GizmoHandle::Ring(i) => AXIS_COLORS[i],
+            GizmoHandle::Center => Color::WHITE,
+        };
+        let rotation = Quat::from_mat3(&Mat3::from_cols(axes[0], axes[1], axes[2]));
+
+        match (settings.mode, handle) {
+            (GizmoMode::Translate, GizmoHandle::Axis(i)) => {
+                gizmos.arrow(origin, origin + axes[i] * size, color);
+            }
+            (GizmoMode::Scale, GizmoHandle::Axis(i)) => {
+                let end = origin + axes[i] * size;
+                gizmos.line(origin, end, color);
+                gizmos.cuboid(
+                    Transform::from_translation(end)
+                        .with_rotation(rotation)
+                        .with_scale(Vec3::splat(size * 0.1)),
+                    color,
+                );
+            }
+            (_, GizmoHandle::Plane(i)) => {
+                let (u, v) = (axes[(i + 1) % 3], axes[(i + 2) % 3]);
+                let center = origin + (u + v) * size * PLANE_HANDLE_OFFSET;
+                let (u, v) = (
+                    u * size * PLANE_HANDLE_HALF_SIZE,
+                    v * size * PLANE_HANDLE_HALF_SIZE,
+                );
+                gizmos.linestrip(
+                    [
+                        center + u + v,
+                        center - u + v,
+                        center - u - v,
+                        center + u - v,
+                        center + u + v,
+                    ],
+                    color,
+                );
+            }
+            (_, GizmoHandle::Ring(i)) => {
+                gizmos.circle(
+                    Isometry3d::new(origin, Quat::from_rotation_arc(Vec3::Z, axes[i])),
+                    size,
+                    color,
+                );
+            }
+            (_, GizmoHandle::Center) => {
+                gizmos.cuboid(
+                    Transform::from_translation(origin)
+                        .with_rotation(rotation)
+                        .with_scale(Vec3::splat(size * 0.15)),
+                    color,
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// Where the selection starts: off the grid and turned, so world and local axes differ.
+    fn start() -> Transform {
+        Transform::from_xyz(0.3, 1.2, -0.4).with_rotation(Quat::from_rotation_y(0.7))
+    }
+
+    /// A gizmo in `mode` and `space` with `handle` grabbed at `grab_offset` from the selection.
+    fn grab(
+        mode: GizmoMode,
+        space: GizmoSpace,
+        handle: GizmoHandle,
+        grab_offset: Vec3,
+    ) -> (TransformGizmo, ActiveDrag) {
+        let settings = TransformGizmo {
+            mode,
+            space,
+            ..default()
+        };
+        let start = start();
+        let drag = ActiveDrag {
+            handle,
+            camera: Entity::PLACEHOLDER,
+            start,
+            axes: settings.axes(&start),
+            grab_point: start.translation + grab_offset,
+            view_direction: Vec3::NEG_Z,
+        };
+        (settings, drag)
+    }
+
+    #[test]
+    fn axis_translation_snaps_to_the_world_grid() {
+        let (mut settings, drag) = grab(
+            GizmoMode::Translate,
+            GizmoSpace::World,
+            GizmoHandle::Axis(0),
+            Vec3::X * 0.1,
+        );
+        // Only the part of the movement along the axis counts.
+        let point = drag.grab_point + Vec3::new(1.0, 0.4, 0.2);
+
+        let moved = dragged_transform(&settings, &drag, point, false);
+        assert!(
+            moved
+                .translation
+                .abs_diff_eq(Vec3::new(1.3, 1.2, -0.4), 1e-5)
+        );
+        assert_eq!(moved.rotation, drag.start.rotation);
+
+        let snapped = dragged_transform(&settings, &drag, point, true);
+        assert!(
+            snapped
+                .translation
+                .abs_diff_eq(Vec3::new(1.5, 1.2, -0.4), 1e-5)
+        );
+
+        // Local axes move by whole grid steps instead, from wherever the selection was.
+        settings.space = GizmoSpace::Local;
+        let local_x = drag.start.rotation * Vec3::X;
+        let drag = ActiveDrag {
+            axes: settings.axes(&drag.start),
+            ..drag
+        };
+        let point = drag.grab_point + local_x * 0.8;
+        let snapped = dragged_transform(&settings, &drag, point, true);
+        assert!(
+            snapped
+                .translation
+                .abs_diff_eq(drag.start.translation + local_x, 1e-5)
+        );
+    }
+
+    #[test]
+    fn plane_translation_moves_in_the_plane_and_snaps_both_axes() {
+        let (settings, drag) = grab(
+            GizmoMode::Translate,
+            GizmoSpace::World,
+            GizmoHandle::Plane(1),
+            Vec3::new(0.05, 0.0, 0.05),
+        );
+        let point = drag.grab_point + Vec3::new(0.8, 2.0, -1.2);
+
+        let moved = dragged_transform(&settings, &drag, point, false);
+        assert!(
+            moved
+                .translation
+                .abs_diff_eq(Vec3::new(1.1, 1.2, -1.6), 1e-5)
+        );
+
+        let snapped = dragged_transform(&settings, &drag, point, true);
+        assert!(
+            snapped
+                .translation
+                .abs_diff_eq(Vec3::new(1.0, 1.2, -1.5), 1e-5)
+        );
+    }
+
+    #[test]
+    fn ring_rotation_turns_by_the_dragged_angle() {
+        let (settings, drag) = grab(
+            GizmoMode::Rotate,
+            GizmoSpace::World,
+            GizmoHandle::Ring(1),
+            Vec3::X,
+        );
+        let turn = Quat::from_rotation_y(0.5);
+        let point = drag.start.translation + turn * Vec3::X * 2.0;
+
+        let turned = dragged_transform(&settings, &drag, point, false);
+        assert!(
+            turned
+                .rotation
+                .abs_diff_eq(turn * drag.start.rotation, 1e-5)
+        );
+        assert_eq!(turned.translation, drag.start.translation);
+
+        let snapped = dragged_transform(&settings, &drag, point, true);
+        let snapped_turn = Quat::from_rotation_y(2.0 * settings.rotate_snap);
+        assert!(
+            snapped
+                .rotation
+                .abs_diff_eq(snapped_turn * drag.start.rotation, 1e-5)
+        );
+    }
+
+    #[test]
+    fn physics_bodies_get_no_scale_handles() {
+        let mut world = World::new();
+        let plain = world.spawn(Transform::default()).id();
+        let body = world.spawn(RigidBody::Dynamic).id();
+        let with_collider_child = world
+            .spawn(Transform::default())
+            .with_child(Collider::sphere(0.5))
+            .id();
+
+        let scalable = world
+            .run_system_once(
+                move |children: Query<&Children>, physics: Query<(), InPhysics>| {
+                    [plain, body, with_collider_child]
+                        .map(|entity| is_scalable(entity, &children, &physics))
+                },
+            )
+            .unwrap();
+        assert_eq!(scalable, [true, false, false]);
+
+        let settings = TransformGizmo {
+            mode: GizmoMode::Scale,
+            ..default()
+        };
+        assert!(settings.handles(false).is_empty());
+        assert!(settings.handles(true).contains(&GizmoHandle::Center));
+    }
+}
@@ -0,0 +1,325 @@
+use bevy::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{
+    orbit_camera::{CameraSettings, PITCH_LIMIT, is_hovered},
+    projection_switch::{ProjectionKind, SetProjection},
+};
+
+/// Blender-style numpad views for [`CameraSettings`] cameras.
+pub struct ViewSnapPlugin;
+
+impl Plugin for ViewSnapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SnapView>().add_systems(
+            Update,
+            (
+                snap_view_hotkeys,
+                start_view_transition,
+                run_view_transition,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The axis-aligned directions a camera can be snapped to, named after the side of the focus
+/// point the camera ends up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisView {
+    Top,
+    Bottom,
+    Front,
+    Back,
+    Left,
+    Right,
+}
+
+impl AxisView {
+    /// The yaw and pitch looking at the focus point from this side.
+    ///
+    /// Top and bottom stop at [`PITCH_LIMIT`] rather than looking straight down or up, for the
+    /// same reason [`CameraSettings`] clamps its pitch.
+    pub fn yaw_pitch(self) -> (f32, f32) {
+        match self {
+            AxisView::Top => (0.0, -PITCH_LIMIT),
+            AxisView::Bottom => (0.0, PITCH_LIMIT),
+            AxisView::Front => (0.0, 0.0),
+            AxisView::Back => (PI, 0.0),
+            AxisView::Left => (-FRAC_PI_2, 0.0),
+            AxisView::Right => (FRAC_PI_2, 0.0),
+        }
+    }
+}
+
+/// Rotates `camera` around its focus point to look from `view`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SnapView {
+    pub camera: Entity,
+    pub view: AxisView,
+    /// Also switch to an orthographic projection.
+    pub orthographic: bool,
+}
+
+/// View snapping [`Component`].
+#[derive(Component, Debug)]
+pub struct ViewSnapControls {
+    /// Seconds a snap takes to rotate to the new view.
+    pub duration: f32,
+    /// Switch to an orthographic projection when snapping with the keyboard.
+    pub orthographic: bool,
+    /// [`KeyCode`] for the front view.
+    pub key_front: KeyCode,
+    /// [`KeyCode`] for the right view.
+    pub key_right: KeyCode,
+    /// [`KeyCode`] for the top view.
+    pub key_top: KeyCode,
+    /// [`KeyCode`] to hold for the opposite view: back, left or bottom.
+    pub key_opposite: KeyCode,
+}
+
+impl Default for ViewSnapControls {
+    fn default() -> Self {
+        Self {
+            duration: 0.3,
+            orthographic: false,
+            key_front: KeyCode::Numpad1,
+            key_right: KeyCode::Numpad3,
+            key_top: KeyCode::Numpad7,
+            key_opposite: KeyCode::ControlLeft,
+        }
+    }
+}
+
+/// An in-progress rotation started by a [`SnapView`] event.
+///
+/// Yaw and pitch are eased separately, so the camera turns about the vertical and never rolls
+/// or passes over the top, even between opposite views.
+#[derive(Component, Debug)]
+pub struct ViewTransition {
+    from_yaw: f32,
+    from_pitch: f32,
+    /// The view's yaw, wrapped to within half a turn of `from_yaw` so the camera turns the
+    /// short way round.
+    to_yaw: f32,
+    view: AxisView,
+    elapsed: f32,
+    duration: f32,
+}
+
+// This system sends `SnapView` events for the camera under the cursor.
+fn snap_view_hotkeys(
+    cameras: Query<(Entity, &Camera, &CameraSettings, &ViewSnapControls)>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window>,
+    mut events: EventWriter<SnapView>,
+) {
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    for (entity, camera, settings, controls) in &cameras {
+        if !settings.enabled || !is_hovered(camera, cursor_position) {
+            continue;
+        }
+
+        let opposite = key_input.pressed(controls.key_opposite);
+        let view = match (
+            key_input.just_pressed(controls.key_front),
+            key_input.just_pressed(controls.key_right),
+            key_input.just_pressed(controls.key_top),
+            opposite,
+        ) {
+            (true, _, _, false) => AxisView::Front,
+            (true, _, _, true) => AxisView::Back,
+            (_, true, _, false) => AxisView::Right,
+            (_, true, _, true) => AxisView::Left,
+            (_, _, true, false) => AxisView::Top,
+            (_, _, true, true) => AxisView::Bottom,
+            _ => continue,
+        };
+
+        events.write(SnapView {
+            camera: entity,
+            view,
+            orthographic: controls.orthographic,
+        });
+    }
+}
+
+fn start_view_transition(
+    mut commands: Commands,
+    mut events: EventReader<SnapView>,
+    cameras: Query<(&CameraSettings, Option<&ViewSnapControls>)>,
+    mut projections: EventWriter<SetProjection>,
+) {
+    for event in events.read() {
+        let Ok((settings, controls)) = cameras.get(event.camera) else {
+            continue;
+        };
+
+        let (from_yaw, from_pitch) = (settings.current.yaw, settings.current.pitch);
+        let (yaw, _) = event.view.yaw_pitch();
+        commands.entity(event.camera).insert(ViewTransition {
+            from_yaw,
+            from_pitch,
+            to_yaw: from_yaw + (yaw - from_yaw + PI).rem_euclid(TAU) - PI,
+            view: event.view,
+            elapsed: 0.0,
+            duration: controls.map_or(ViewSnapControls::default().duration, |c| c.duration),
+        });
+
+        if event.orthographic {
+            projections.write(SetProjection {
+                camera: event.camera,
+                kind: ProjectionKind::Orthographic,
+            });
+        }
+    }
+}
+
+fn run_view_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cameras: Query<(Entity, &mut CameraSettings, &mut ViewTransition)>,
+) {
+    for (entity, mut settings, mut transition) in &mut cameras {
+        transition.elapsed += time.delta_secs();
+        let t = if transition.duration > 0.0 {
+            (transition.elapsed / transition.duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        let (yaw, pitch) = if t < 1.0 {
+            let eased = t * t * (3.0 - 2.0 * t);
+            let (_, to_pitch) = transition.view.yaw_pitch();
+            (
+                transition.from_yaw.lerp(transition.to_yaw, eased),
+                transition.from_pitch.lerp(to_pitch, eased),
+            )
+        } else {
+            commands.entity(entity).remove::<ViewTransition>();
+            transition.view.yaw_pitch()
+        };
+
+        // The transition drives the displayed pose directly; easing on top would only lag it.
+        settings.yaw = yaw;
+        settings.pitch = pitch;
+        settings.current.yaw = yaw;
+        settings.current.pitch = pitch;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestApp;
+
+    const VIEWS: [AxisView; 6] = [
+        AxisView::Top,
+        AxisView::Bottom,
+        AxisView::Front,
+        AxisView::Back,
+        AxisView::Left,
+        AxisView::Right,
+    ];
+
+    fn snap(app: &mut TestApp, view: AxisView) {
+        let camera = app.camera();
+        app.world_mut().send_event(SnapView {
+            camera,
+            view,
+            orthographic: false,
+        });
+    }
+
+    fn is_transitioning(app: &TestApp) -> bool {
+        app.world().get::<ViewTransition>(app.camera()).is_some()
+    }
+
+    #[test]
+    fn every_snap_ends_at_its_view() {
+        for view in VIEWS {
+            let mut app = TestApp::looking_at_origin();
+            snap(&mut app, view);
+            app.settle();
+
+            let (yaw, pitch) = view.yaw_pitch();
+            let settings = app.settings();
+            assert!(!is_transitioning(&app), "{view:?} never finished");
+            let ended_at = Vec2::new(settings.current.yaw, settings.current.pitch);
+            assert!(
+                ended_at.abs_diff_eq(Vec2::new(yaw, pitch), 1e-5),
+                "{view:?} ended at yaw and pitch {ended_at}"
+            );
+            let expected = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+            let rotation = app.transform().rotation;
+            assert!(
+                rotation.abs_diff_eq(expected, 1e-5),
+                "{view:?} ended at {rotation}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn snapping_to_the_opposite_view_turns_about_the_vertical() {
+        // Straight from the front, and from slightly above it, where the shortest arc to the
+        // back view would lead over the top.
+        for start_pitch in [0.0, -0.05] {
+            let mut app = TestApp::looking_at_origin();
+            let mut settings = app.settings_mut();
+            settings.yaw = 0.0;
+            settings.pitch = start_pitch;
+            app.settle();
+
+            snap(&mut app, AxisView::Back);
+            app.update();
+            let mut frames = 0;
+            while is_transitioning(&app) {
+                let right = app.transform().right();
+                let pitch = app.settings().current.pitch;
+                assert!(
+                    right.y.abs() < 1e-5,
+                    "the camera rolled: right is {}",
+                    *right
+                );
+                assert!(pitch.abs() <= PITCH_LIMIT, "pitch {pitch}");
+                assert!(
+                    (start_pitch - 1e-5..=1e-5).contains(&pitch),
+                    "pitch {pitch} on the way from {start_pitch} to the back view"
+                );
+                frames += 1;
+                app.update();
+            }
+
+            assert!(frames > 1, "the transition should take several frames");
+            let (yaw, pitch) = AxisView::Back.yaw_pitch();
+            let settings = app.settings();
+            assert!((settings.current.yaw - yaw).abs() < 1e-5);
+            assert_eq!(settings.current.pitch, pitch);
+        }
+    }
+
+    #[test]
+    fn snapping_turns_the_short_way_round() {
+        let mut app = TestApp::looking_at_origin();
+        // Level, a full turn and a bit to the left of the front view.
+        let mut settings = app.settings_mut();
+        settings.yaw = TAU + 0.5;
+        settings.pitch = 0.0;
+        app.settle();
+
+        snap(&mut app, AxisView::Front);
+        let mut largest_turn: f32 = 0.0;
+        let start = app.transform().forward();
+        while {
+            app.update();
+            is_transitioning(&app)
+        } {
+            largest_turn = largest_turn.max(app.transform().forward().angle_between(*start));
+        }
+
+        assert!(largest_turn <= 0.5 + 1e-4, "turned {largest_turn} rad away");
+    }
+}